@@ -1,12 +1,11 @@
 use byteorder::{BigEndian, ReadBytesExt};
-use log;
-use pretty_env_logger;
 use rand::{thread_rng, Rng};
 use std::fs::File;
 use std::io::Cursor;
 use std::io::{BufReader, Error, Read};
 
 use ann_rs::activators::Relu;
+use ann_rs::callbacks::ProgressBar;
 use ann_rs::functions::*;
 use ann_rs::objectives::CrossEntropy;
 use ann_rs::optimizers::Adam;
@@ -31,11 +30,12 @@ fn print_sample_image(image: &[u8], rows: usize, cols: usize, label: u8) {
                 print!("##");
             }
         }
-        print!("\n");
+        println!();
     }
     println!("Sample image label: {}", label);
 }
 
+#[allow(clippy::type_complexity)]
 fn read_image_and_labels(
     image_file: &str,
     label_file: &str,
@@ -112,6 +112,7 @@ fn main() -> Result<(), Error> {
         .output(10)
         .minimize_to(CrossEntropy::new())
         .optimize_with(Adam::new(0.001))
+        .with_callback(ProgressBar::new())
         .build();
 
     let image_data: Vec<Vec<f64>> = image_data.chunks(rows * cols).map(|s| s.to_vec()).collect();
@@ -1,5 +1,3 @@
-use pretty_env_logger;
-
 use ann_rs::activators::Sigmoid;
 // use ann_rs::functions::xavier_init;
 use ann_rs::objectives::BinaryCrossEntropy;
@@ -14,14 +14,14 @@ impl Activator for Sigmoid {
             .collect()
     }
 
-    // f'(x)=f(x)(1-f(x))
+    // f'(x)=f(x)(1-f(x)), where the outputs are f(x) already
     //
     // outputs: minibatch of outputs of current layer
     // return: minibatch of derivs of current layer
     fn derived(&self, outputs: &[Vec<f64>]) -> Vec<Vec<f64>> {
         outputs
             .iter()
-            .map(|x| x.iter().map(|&x| sigmoid(x) * (1. - sigmoid(x))).collect())
+            .map(|x| x.iter().map(|&y| y * (1. - y)).collect())
             .collect()
     }
 }
@@ -36,4 +36,13 @@ mod tests {
         let result = Sigmoid.activate(&x);
         assert_eq!(result, [[0.9706877692486436]]);
     }
+
+    #[test]
+    fn test_sigmoid_derived() {
+        let outputs = Sigmoid.activate(&[vec![0., 3.5]]);
+        let derived = Sigmoid.derived(&outputs);
+        // f'(0) = 0.25, f'(3.5) = f(3.5) * (1 - f(3.5))
+        assert!((derived[0][0] - 0.25).abs() < 1e-12);
+        assert!((derived[0][1] - 0.9706877692486436 * 0.0293122307513564).abs() < 1e-12);
+    }
 }
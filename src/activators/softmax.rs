@@ -16,7 +16,7 @@ impl Activator for Softmax {
             .map(|x| {
                 let max = x
                     .iter()
-                    .max_by(|a, b| a.partial_cmp(&b).unwrap_or(Ordering::Equal))
+                    .max_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal))
                     .unwrap();
 
                 let exps: Vec<f64> = x.iter().map(|x| (x - max).exp()).collect();
@@ -0,0 +1,157 @@
+use super::{Callback, EpochLogs, FitParams, Monitor};
+use crate::activators::Activator;
+use crate::network::Network;
+use crate::objectives::Objective;
+use crate::optimizers::Optimizer;
+
+// stops fit once the monitored metric has not improved by more than min_delta
+// for patience epochs, and optionally restores the weights of the best epoch
+pub struct EarlyStopping {
+    // None monitors the validation loss, or the loss without validation data
+    monitor: Option<Monitor>,
+    patience: usize,
+    min_delta: f64,
+    restore_best_weights: bool,
+    best: Option<f64>,
+    best_parameters: Option<Vec<f64>>,
+    num_bad_epochs: usize,
+    stopped_epoch: Option<usize>,
+}
+
+impl EarlyStopping {
+    pub fn new(patience: usize) -> EarlyStopping {
+        EarlyStopping {
+            monitor: None,
+            patience,
+            min_delta: 0.,
+            restore_best_weights: false,
+            best: None,
+            best_parameters: None,
+            num_bad_epochs: 0,
+            stopped_epoch: None,
+        }
+    }
+
+    // the validation loss by default, or the loss when fit has no validation data
+    pub fn with_monitor(mut self, monitor: Monitor) -> EarlyStopping {
+        self.monitor = Some(monitor);
+        self
+    }
+
+    // smaller changes of the metric are not improvements
+    pub fn with_min_delta(mut self, min_delta: f64) -> EarlyStopping {
+        assert!(min_delta >= 0., "min delta should not be negative");
+        self.min_delta = min_delta;
+        self
+    }
+
+    // restore the weights of the best epoch at the end of fit
+    pub fn with_restore_best_weights(mut self) -> EarlyStopping {
+        self.restore_best_weights = true;
+        self
+    }
+
+    // epoch fit stopped after, None if it ran all epochs
+    pub fn stopped_epoch(&self) -> Option<usize> {
+        self.stopped_epoch
+    }
+
+    fn value(&self, logs: &EpochLogs) -> f64 {
+        match &self.monitor {
+            Some(monitor) => monitor.value(logs),
+            None => logs.val_loss.unwrap_or(logs.loss),
+        }
+    }
+
+    fn is_improvement(&self, value: f64) -> bool {
+        let higher_is_better = self.monitor.as_ref().is_some_and(Monitor::higher_is_better);
+        match self.best {
+            None => true,
+            Some(best) if higher_is_better => value > best + self.min_delta,
+            Some(best) => value < best - self.min_delta,
+        }
+    }
+}
+
+impl<A: Activator, Obj: Objective<A>, Opt: Optimizer> Callback<A, Obj, Opt> for EarlyStopping {
+    fn on_train_begin(&mut self, _: &mut Network<A, Obj, Opt>, _: &FitParams) {
+        self.best = None;
+        self.best_parameters = None;
+        self.num_bad_epochs = 0;
+        self.stopped_epoch = None;
+    }
+
+    fn on_epoch_end(&mut self, network: &mut Network<A, Obj, Opt>, epoch: usize, logs: &EpochLogs) {
+        let value = self.value(logs);
+        if self.is_improvement(value) {
+            self.best = Some(value);
+            self.num_bad_epochs = 0;
+            if self.restore_best_weights {
+                self.best_parameters = Some(network.parameters());
+            }
+            return;
+        }
+
+        self.num_bad_epochs += 1;
+        if self.num_bad_epochs >= self.patience {
+            log::info!("early stopping after epoch {}", epoch);
+            self.stopped_epoch = Some(epoch);
+            network.stop_training(&format!(
+                "early stopping: no improvement for {} epochs",
+                self.num_bad_epochs
+            ));
+        }
+    }
+
+    fn on_train_end(&mut self, network: &mut Network<A, Obj, Opt>) {
+        if let Some(best_parameters) = self.best_parameters.take() {
+            network.set_parameters(&best_parameters);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objectives::BinaryCrossEntropy;
+    use crate::optimizers::SGD;
+    use crate::NetworkBuilder;
+
+    #[test]
+    fn test_early_stopping() {
+        let mut nn = NetworkBuilder::new()
+            .input(2)
+            .output(1)
+            .minimize_to(BinaryCrossEntropy::new())
+            .optimize_with(SGD::new(0.1))
+            .build();
+        let mut early_stopping = EarlyStopping::new(2)
+            .with_min_delta(0.1)
+            .with_restore_best_weights();
+        // the validation loss is monitored by default
+        let logs = |val_loss| EpochLogs {
+            loss: 0.,
+            accuracy: 0.,
+            val_loss: Some(val_loss),
+            val_accuracy: None,
+        };
+
+        let params = FitParams {
+            epochs: 3,
+            batch_size: 4,
+            num_samples: 4,
+            num_batches: 1,
+        };
+        early_stopping.on_train_begin(&mut nn, &params);
+        early_stopping.on_epoch_end(&mut nn, 0, &logs(1.));
+        let best = nn.parameters();
+        nn.set_parameters(&vec![0.; best.len()]);
+        // not better by min delta, the first of 2 epochs of patience
+        early_stopping.on_epoch_end(&mut nn, 1, &logs(0.95));
+        assert_eq!(early_stopping.stopped_epoch(), None);
+        early_stopping.on_epoch_end(&mut nn, 2, &logs(1.2));
+        assert_eq!(early_stopping.stopped_epoch(), Some(2));
+        early_stopping.on_train_end(&mut nn);
+        assert_eq!(nn.parameters(), best);
+    }
+}
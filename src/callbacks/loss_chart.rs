@@ -0,0 +1,61 @@
+use super::{BatchLogs, Callback, FitParams};
+use crate::activators::Activator;
+use crate::network::Network;
+use crate::objectives::Objective;
+use crate::optimizers::Optimizer;
+
+use textplots::{Chart, Plot, Shape};
+
+// draws the loss of every minibatch on stdout at the end of fit
+pub struct LossChart {
+    width: u32,
+    height: u32,
+    losses: Vec<f64>,
+}
+
+impl Default for LossChart {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LossChart {
+    pub fn new() -> LossChart {
+        LossChart {
+            width: 180,
+            height: 100,
+            losses: vec![],
+        }
+    }
+
+    // size of the chart in braille dots
+    pub fn with_size(mut self, width: u32, height: u32) -> LossChart {
+        self.width = width;
+        self.height = height;
+        self
+    }
+}
+
+impl<A: Activator, Obj: Objective<A>, Opt: Optimizer> Callback<A, Obj, Opt> for LossChart {
+    fn on_train_begin(&mut self, _: &mut Network<A, Obj, Opt>, _: &FitParams) {
+        self.losses.clear();
+    }
+
+    fn on_batch_end(&mut self, _: &mut Network<A, Obj, Opt>, _: usize, _: usize, logs: &BatchLogs) {
+        self.losses.push(logs.loss);
+    }
+
+    fn on_train_end(&mut self, _: &mut Network<A, Obj, Opt>) {
+        println!("Loss:");
+        let losses: Vec<(f32, f32)> = self
+            .losses
+            .iter()
+            .enumerate()
+            .map(|(i, &v)| (i as f32, v as f32))
+            .collect();
+        let xmax = self.losses.len() as f32;
+        Chart::new(self.width, self.height, 0., xmax)
+            .lineplot(&Shape::Lines(&losses))
+            .nice();
+    }
+}
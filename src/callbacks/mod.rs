@@ -0,0 +1,114 @@
+mod early_stopping;
+#[cfg(feature = "chart")]
+mod loss_chart;
+mod progress_bar;
+
+pub use early_stopping::EarlyStopping;
+#[cfg(feature = "chart")]
+pub use loss_chart::LossChart;
+pub use progress_bar::ProgressBar;
+
+use crate::activators::Activator;
+use crate::network::Network;
+use crate::objectives::Objective;
+use crate::optimizers::Optimizer;
+
+// what fit trains on
+pub struct FitParams {
+    pub epochs: usize,
+    pub batch_size: usize,
+    // training samples, without held out validation samples
+    pub num_samples: usize,
+    // minibatches per epoch
+    pub num_batches: usize,
+}
+
+// metrics of one minibatch
+pub struct BatchLogs {
+    // weighted mean loss
+    pub loss: f64,
+    pub accuracy: f64,
+    pub num_samples: usize,
+    // L2 norm of the mean gradients before clipping, see Network::gradient_norm
+    pub gradient_norm: f64,
+}
+
+// metrics of one epoch over all its minibatches, and of the validation data
+// evaluated after it, if any
+pub struct EpochLogs {
+    // weighted mean loss
+    pub loss: f64,
+    pub accuracy: f64,
+    pub val_loss: Option<f64>,
+    pub val_accuracy: Option<f64>,
+}
+
+// metric of the epoch watched by callbacks, eg. EarlyStopping
+pub enum Monitor {
+    Loss,
+    Accuracy,
+    ValLoss,
+    ValAccuracy,
+}
+
+impl Monitor {
+    pub fn value(&self, logs: &EpochLogs) -> f64 {
+        match self {
+            Monitor::Loss => logs.loss,
+            Monitor::Accuracy => logs.accuracy,
+            Monitor::ValLoss => logs
+                .val_loss
+                .expect("validation loss is monitored without validation data"),
+            Monitor::ValAccuracy => logs
+                .val_accuracy
+                .expect("validation accuracy is monitored without validation data"),
+        }
+    }
+
+    // whether the metric improves by increasing
+    pub fn higher_is_better(&self) -> bool {
+        match self {
+            Monitor::Loss | Monitor::ValLoss => false,
+            Monitor::Accuracy | Monitor::ValAccuracy => true,
+        }
+    }
+}
+
+// hooks into Network::fit, eg. for custom logging, checkpointing and scheduling
+// every hook gets mutable access to the network, and so to its optimizer
+// fit reports nothing to the console unless a reporter is added, eg.
+// ProgressBar or LossChart
+// epoch: epochs done before this one over all fits
+// batch: index of the minibatch within the epoch
+pub trait Callback<A: Activator, Obj: Objective<A>, Opt: Optimizer> {
+    fn on_train_begin(&mut self, _network: &mut Network<A, Obj, Opt>, _params: &FitParams) {}
+
+    fn on_epoch_begin(&mut self, _network: &mut Network<A, Obj, Opt>, _epoch: usize) {}
+
+    fn on_batch_begin(
+        &mut self,
+        _network: &mut Network<A, Obj, Opt>,
+        _epoch: usize,
+        _batch: usize,
+    ) {
+    }
+
+    fn on_batch_end(
+        &mut self,
+        _network: &mut Network<A, Obj, Opt>,
+        _epoch: usize,
+        _batch: usize,
+        _logs: &BatchLogs,
+    ) {
+    }
+
+    fn on_epoch_end(
+        &mut self,
+        _network: &mut Network<A, Obj, Opt>,
+        _epoch: usize,
+        _logs: &EpochLogs,
+    ) {
+    }
+
+    fn on_train_end(&mut self, _network: &mut Network<A, Obj, Opt>) {}
+}
@@ -0,0 +1,179 @@
+use super::{BatchLogs, Callback, EpochLogs, FitParams};
+use crate::activators::Activator;
+use crate::network::Network;
+use crate::objectives::Objective;
+use crate::optimizers::Optimizer;
+
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+// redraws a progress bar of the epoch with the time left for the fit on stderr
+// after every minibatch, and keeps one line of metrics per epoch
+//     epoch 3/10 [==========>         ] 12/24 loss:0.1234 acc:0.9000 eta:0:42
+pub struct ProgressBar {
+    width: usize,
+    epochs: usize,
+    num_batches: usize,
+    // minibatches done in this fit
+    batches_done: usize,
+    // epochs done in this fit
+    epochs_done: usize,
+    start: Instant,
+    epoch_start: Instant,
+}
+
+impl Default for ProgressBar {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProgressBar {
+    pub fn new() -> ProgressBar {
+        ProgressBar {
+            width: 30,
+            epochs: 0,
+            num_batches: 0,
+            batches_done: 0,
+            epochs_done: 0,
+            start: Instant::now(),
+            epoch_start: Instant::now(),
+        }
+    }
+
+    // width of the bar in characters
+    pub fn with_width(mut self, width: usize) -> ProgressBar {
+        self.width = width;
+        self
+    }
+
+    fn bar(&self, batches: usize) -> String {
+        let filled = self.width * batches / self.num_batches.max(1);
+        let mut bar = "=".repeat(filled);
+        if filled < self.width {
+            bar.push('>');
+            bar.push_str(&" ".repeat(self.width - filled - 1));
+        }
+        bar
+    }
+
+    // time left at the mean time of the minibatches done so far,
+    // assuming fit runs all epochs, None if it is too long for a Duration
+    fn eta(&self) -> Option<Duration> {
+        let batches_left = self
+            .epochs
+            .saturating_mul(self.num_batches)
+            .saturating_sub(self.batches_done);
+        let seconds = self.start.elapsed().as_secs_f64() / self.batches_done.max(1) as f64
+            * batches_left as f64;
+        Duration::try_from_secs_f64(seconds).ok()
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+impl<A: Activator, Obj: Objective<A>, Opt: Optimizer> Callback<A, Obj, Opt> for ProgressBar {
+    fn on_train_begin(&mut self, _: &mut Network<A, Obj, Opt>, params: &FitParams) {
+        self.epochs = params.epochs;
+        self.num_batches = params.num_batches;
+        self.batches_done = 0;
+        self.epochs_done = 0;
+        self.start = Instant::now();
+    }
+
+    fn on_epoch_begin(&mut self, _: &mut Network<A, Obj, Opt>, _: usize) {
+        self.epoch_start = Instant::now();
+    }
+
+    fn on_batch_end(
+        &mut self,
+        _: &mut Network<A, Obj, Opt>,
+        _: usize,
+        batch: usize,
+        logs: &BatchLogs,
+    ) {
+        self.batches_done += 1;
+        let eta = match self.eta() {
+            Some(eta) => format!(" eta:{}", format_duration(eta)),
+            None => String::new(),
+        };
+        let mut stderr = io::stderr();
+        let _ = write!(
+            stderr,
+            "\repoch {}/{} [{}] {}/{} loss:{:.4} acc:{:.4}{}",
+            self.epochs_done + 1,
+            self.epochs,
+            self.bar(batch + 1),
+            batch + 1,
+            self.num_batches,
+            logs.loss,
+            logs.accuracy,
+            eta,
+        );
+        let _ = stderr.flush();
+    }
+
+    fn on_epoch_end(&mut self, _: &mut Network<A, Obj, Opt>, _: usize, logs: &EpochLogs) {
+        self.epochs_done += 1;
+        let validation = match (logs.val_loss, logs.val_accuracy) {
+            (Some(val_loss), Some(val_accuracy)) => {
+                format!(" val_loss:{:.4} val_acc:{:.4}", val_loss, val_accuracy)
+            }
+            _ => String::new(),
+        };
+        // pad over the rest of the last minibatch's line
+        eprintln!(
+            "\repoch {}/{} [{}] {}/{} loss:{:.4} acc:{:.4}{} {:.1}s          ",
+            self.epochs_done,
+            self.epochs,
+            self.bar(self.num_batches),
+            self.num_batches,
+            self.num_batches,
+            logs.loss,
+            logs.accuracy,
+            validation,
+            self.epoch_start.elapsed().as_secs_f64(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_bar() {
+        let progress_bar = ProgressBar {
+            num_batches: 4,
+            ..ProgressBar::new().with_width(8)
+        };
+        assert_eq!(progress_bar.bar(1), "==>     ");
+        assert_eq!(progress_bar.bar(4), "========");
+        assert_eq!(format_duration(Duration::from_secs(125)), "2:05");
+    }
+
+    #[test]
+    fn test_eta() {
+        let start = Instant::now() - Duration::from_secs(10);
+        let progress_bar = ProgressBar {
+            epochs: 2,
+            num_batches: 4,
+            batches_done: 4,
+            start,
+            ..ProgressBar::new()
+        };
+        assert_eq!(progress_bar.eta().unwrap().as_secs(), 10);
+        // too many minibatches left for a Duration
+        let progress_bar = ProgressBar {
+            epochs: usize::MAX,
+            num_batches: 2,
+            batches_done: 1,
+            start,
+            ..ProgressBar::new()
+        };
+        assert_eq!(progress_bar.eta(), None);
+    }
+}
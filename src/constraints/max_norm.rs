@@ -0,0 +1,44 @@
+use super::{norm, Constraint};
+
+// http://jmlr.org/papers/v15/srivastava14a.html
+//
+// rescales each node's incoming weights whose L2 norm is over max_norm to max_norm
+#[derive(Debug)]
+pub struct MaxNorm {
+    max_norm: f64,
+}
+
+impl MaxNorm {
+    pub fn new(max_norm: f64) -> MaxNorm {
+        assert!(max_norm > 0., "max norm should be positive");
+        MaxNorm { max_norm }
+    }
+}
+
+impl Constraint for MaxNorm {
+    fn apply(&self, weights: &mut [Vec<f64>]) {
+        weights.iter_mut().for_each(|row| {
+            let norm = norm(row);
+            if norm > self.max_norm {
+                row.iter_mut().for_each(|w| *w *= self.max_norm / norm);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_max_norm() {
+        let mut weights = vec![vec![3., -4.], vec![0.3, 0.4]];
+        MaxNorm::new(1.).apply(&mut weights);
+        let expected = [[0.6, -0.8], [0.3, 0.4]];
+        for (row, expected) in weights.iter().zip(expected.iter()) {
+            for (w, expected) in row.iter().zip(expected.iter()) {
+                assert!((w - expected).abs() < 1e-12);
+            }
+        }
+    }
+}
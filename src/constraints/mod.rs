@@ -0,0 +1,21 @@
+mod max_norm;
+mod non_neg;
+mod unit_norm;
+
+pub use max_norm::MaxNorm;
+pub use non_neg::NonNeg;
+pub use unit_norm::UnitNorm;
+
+use std::fmt::Debug;
+
+// projects a layer's weights (not bias) back into a feasible set after every
+// optimizer step
+pub trait Constraint: Debug {
+    // weights: one layer's weights, each row is a node's incoming weights
+    fn apply(&self, weights: &mut [Vec<f64>]);
+}
+
+// L2 norm of a node's incoming weights
+fn norm(row: &[f64]) -> f64 {
+    row.iter().map(|w| w * w).sum::<f64>().sqrt()
+}
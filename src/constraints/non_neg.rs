@@ -0,0 +1,23 @@
+use super::Constraint;
+
+// clamps negative weights to 0
+#[derive(Debug)]
+pub struct NonNeg;
+
+impl Constraint for NonNeg {
+    fn apply(&self, weights: &mut [Vec<f64>]) {
+        weights.iter_mut().flatten().for_each(|w| *w = w.max(0.));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_non_neg() {
+        let mut weights = vec![vec![3., -4.], vec![-0., 0.4]];
+        NonNeg.apply(&mut weights);
+        assert_eq!(weights, [[3., 0.], [0., 0.4]]);
+    }
+}
@@ -0,0 +1,31 @@
+use super::{norm, Constraint};
+
+// rescales each node's incoming weights to L2 norm 1
+#[derive(Debug)]
+pub struct UnitNorm;
+
+impl Constraint for UnitNorm {
+    fn apply(&self, weights: &mut [Vec<f64>]) {
+        weights.iter_mut().for_each(|row| {
+            let norm = norm(row).max(1e-12);
+            row.iter_mut().for_each(|w| *w /= norm);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unit_norm() {
+        let mut weights = vec![vec![3., -4.], vec![0.3, 0.4], vec![0., 0.]];
+        UnitNorm.apply(&mut weights);
+        let expected = [[0.6, -0.8], [0.6, 0.8], [0., 0.]];
+        for (row, expected) in weights.iter().zip(expected.iter()) {
+            for (w, expected) in row.iter().zip(expected.iter()) {
+                assert!((w - expected).abs() < 1e-12);
+            }
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use super::{evaluate, Evolution};
+use crate::activators::Activator;
+use crate::functions::standard_normal;
+use crate::network::Network;
+use crate::objectives::Objective;
+use crate::optimizers::Optimizer;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::cmp::Ordering;
+
+pub struct EvolutionStrategies {
+    sigma: f64,
+    learning_rate: f64,
+    population_size: usize,
+    rng: StdRng,
+}
+
+impl EvolutionStrategies {
+    // sigma: stddev of the perturbations
+    // learning_rate: step size of the estimated fitness gradient
+    pub fn new(sigma: f64, learning_rate: f64) -> EvolutionStrategies {
+        EvolutionStrategies {
+            sigma,
+            learning_rate,
+            population_size: 50,
+            rng: StdRng::from_entropy(),
+        }
+    }
+
+    // number of perturbations per generation, rounded up to even for the
+    // mirrored pairs
+    pub fn with_population_size(mut self, population_size: usize) -> EvolutionStrategies {
+        assert!(population_size > 0, "population size should be positive");
+        self.population_size = population_size;
+        self
+    }
+
+    // the same seed gives the same perturbations, for reproducible runs
+    pub fn with_seed(mut self, seed: u64) -> EvolutionStrategies {
+        self.rng = StdRng::seed_from_u64(seed);
+        self
+    }
+}
+
+// centered ranks in [-0.5, 0.5], robust to the scale and outliers of fitness
+fn centered_ranks(fitnesses: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..fitnesses.len()).collect();
+    order.sort_by(|&i, &j| {
+        fitnesses[i]
+            .partial_cmp(&fitnesses[j])
+            .unwrap_or(Ordering::Equal)
+    });
+    let mut ranks = vec![0.; fitnesses.len()];
+    let max_rank = (fitnesses.len() - 1).max(1) as f64;
+    for (rank, &i) in order.iter().enumerate() {
+        ranks[i] = rank as f64 / max_rank - 0.5;
+    }
+    ranks
+}
+
+// https://arxiv.org/abs/1703.03864
+//
+// each generation evaluates mirrored perturbations parameters +- sigma * noise
+// and follows the estimated gradient of fitness
+//     parameters += learning_rate / (n * sigma) * SUM(rank(fitness(i)) * noise(i))
+impl Evolution for EvolutionStrategies {
+    fn evolve<A, Obj, Opt, F>(
+        &mut self,
+        network: &mut Network<A, Obj, Opt>,
+        generations: usize,
+        mut fitness: F,
+    ) -> Vec<f64>
+    where
+        A: Activator,
+        Obj: Objective<A>,
+        Opt: Optimizer,
+        F: FnMut(&mut Network<A, Obj, Opt>) -> f64,
+    {
+        let sigma = self.sigma;
+        let rng = &mut self.rng;
+        let mut parameters = network.trainable_parameters();
+        let num_pairs = self.population_size.div_ceil(2);
+        let mut all_fitness = vec![];
+        for i in 0..generations {
+            let noises: Vec<Vec<f64>> = (0..num_pairs)
+                .map(|_| parameters.iter().map(|_| standard_normal(rng)).collect())
+                .collect();
+            let mut fitnesses = vec![];
+            for noise in &noises {
+                for sign in [1., -1.] {
+                    let candidate: Vec<f64> = parameters
+                        .iter()
+                        .zip(noise.iter())
+                        .map(|(p, n)| p + sign * sigma * n)
+                        .collect();
+                    fitnesses.push(evaluate(network, &candidate, &mut fitness));
+                }
+            }
+
+            let ranks = centered_ranks(&fitnesses);
+            let scale = self.learning_rate / (fitnesses.len() as f64 * self.sigma);
+            for (noise, ranks) in noises.iter().zip(ranks.chunks(2)) {
+                // mirrored pair shares its noise
+                let weight = scale * (ranks[0] - ranks[1]);
+                parameters
+                    .iter_mut()
+                    .zip(noise.iter())
+                    .for_each(|(p, n)| *p += weight * n);
+            }
+
+            let generation_fitness = evaluate(network, &parameters, &mut fitness);
+            log::info!("generation:[{}, fitness:{:.3}]", i, generation_fitness);
+            all_fitness.push(generation_fitness);
+        }
+        network.set_trainable_parameters(&parameters);
+        all_fitness
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evolution::tests::network_and_fitness;
+
+    #[test]
+    fn test_evolution_strategies() {
+        let (mut network, mut fitness) = network_and_fitness();
+        let initial = fitness(&mut network);
+        let all_fitness = EvolutionStrategies::new(0.1, 0.05).with_seed(0).evolve(
+            &mut network,
+            100,
+            &mut fitness,
+        );
+        assert!(*all_fitness.last().unwrap() > initial);
+        assert!(fitness(&mut network) > -0.01);
+    }
+}
@@ -0,0 +1,171 @@
+use super::{evaluate, Evolution};
+use crate::activators::Activator;
+use crate::functions::standard_normal;
+use crate::network::Network;
+use crate::objectives::Objective;
+use crate::optimizers::Optimizer;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cmp::Ordering;
+
+pub struct GeneticAlgorithm {
+    population_size: usize,
+    mutation_stddev: f64,
+    mutation_rate: f64,
+    elites: usize,
+    tournament_size: usize,
+    rng: StdRng,
+}
+
+impl GeneticAlgorithm {
+    // mutation_stddev: stddev of the gaussian noise added to mutated parameters
+    pub fn new(population_size: usize, mutation_stddev: f64) -> GeneticAlgorithm {
+        assert!(
+            population_size > 1,
+            "population needs at least 2 individuals"
+        );
+        GeneticAlgorithm {
+            population_size,
+            mutation_stddev,
+            mutation_rate: 0.1,
+            elites: 1,
+            tournament_size: 3,
+            rng: StdRng::from_entropy(),
+        }
+    }
+
+    // probability of each parameter to be mutated
+    pub fn with_mutation_rate(mut self, mutation_rate: f64) -> GeneticAlgorithm {
+        self.mutation_rate = mutation_rate;
+        self
+    }
+
+    // fittest individuals kept as they are in the next generation
+    pub fn with_elites(mut self, elites: usize) -> GeneticAlgorithm {
+        assert!(
+            elites < self.population_size,
+            "elites should be fewer than population"
+        );
+        self.elites = elites;
+        self
+    }
+
+    // individuals competing to be a parent
+    pub fn with_tournament_size(mut self, tournament_size: usize) -> GeneticAlgorithm {
+        assert!(tournament_size > 0, "tournament size should be positive");
+        self.tournament_size = tournament_size;
+        self
+    }
+
+    // the same seed gives the same populations, for reproducible runs
+    pub fn with_seed(mut self, seed: u64) -> GeneticAlgorithm {
+        self.rng = StdRng::seed_from_u64(seed);
+        self
+    }
+}
+
+fn mutate<R: Rng>(rng: &mut R, parameters: &mut [f64], mutation_rate: f64, mutation_stddev: f64) {
+    parameters.iter_mut().for_each(|p| {
+        if rng.gen::<f64>() < mutation_rate {
+            *p += mutation_stddev * standard_normal(rng);
+        }
+    });
+}
+
+// each generation keeps the elites, and breeds the rest from parents picked by
+// tournament with uniform crossover and gaussian mutation
+// the first population is the network's weights and mutations of them
+impl Evolution for GeneticAlgorithm {
+    fn evolve<A, Obj, Opt, F>(
+        &mut self,
+        network: &mut Network<A, Obj, Opt>,
+        generations: usize,
+        mut fitness: F,
+    ) -> Vec<f64>
+    where
+        A: Activator,
+        Obj: Objective<A>,
+        Opt: Optimizer,
+        F: FnMut(&mut Network<A, Obj, Opt>) -> f64,
+    {
+        let (mutation_rate, mutation_stddev) = (self.mutation_rate, self.mutation_stddev);
+        let tournament_size = self.tournament_size;
+        let rng = &mut self.rng;
+        let parameters = network.trainable_parameters();
+        let mut population: Vec<Vec<f64>> = (0..self.population_size)
+            .map(|i| {
+                let mut individual = parameters.clone();
+                if i > 0 {
+                    mutate(rng, &mut individual, mutation_rate, mutation_stddev);
+                }
+                individual
+            })
+            .collect();
+
+        let mut all_fitness = vec![];
+        let mut fittest = parameters;
+        for i in 0..generations {
+            let fitnesses: Vec<f64> = population
+                .iter()
+                .map(|individual| evaluate(network, individual, &mut fitness))
+                .collect();
+            // fittest first
+            let mut order: Vec<usize> = (0..population.len()).collect();
+            order.sort_by(|&i, &j| {
+                fitnesses[j]
+                    .partial_cmp(&fitnesses[i])
+                    .unwrap_or(Ordering::Equal)
+            });
+            fittest = population[order[0]].clone();
+            log::info!("generation:[{}, fitness:{:.3}]", i, fitnesses[order[0]]);
+            all_fitness.push(fitnesses[order[0]]);
+
+            // the fittest of tournament_size random individuals
+            let tournament = |rng: &mut StdRng| {
+                let rank = (0..tournament_size)
+                    .map(|_| rng.gen_range(0, order.len()))
+                    .min()
+                    .unwrap();
+                order[rank]
+            };
+            let mut next_population: Vec<Vec<f64>> = order[..self.elites]
+                .iter()
+                .map(|&i| population[i].clone())
+                .collect();
+            while next_population.len() < self.population_size {
+                let (mother, father) = (tournament(rng), tournament(rng));
+                let mut child: Vec<f64> = population[mother]
+                    .iter()
+                    .zip(population[father].iter())
+                    .map(|(&m, &f)| if rng.gen::<bool>() { m } else { f })
+                    .collect();
+                mutate(rng, &mut child, mutation_rate, mutation_stddev);
+                next_population.push(child);
+            }
+            population = next_population;
+        }
+        network.set_trainable_parameters(&fittest);
+        all_fitness
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evolution::tests::network_and_fitness;
+
+    #[test]
+    fn test_genetic_algorithm() {
+        let (mut network, mut fitness) = network_and_fitness();
+        let initial = fitness(&mut network);
+        let all_fitness = GeneticAlgorithm::new(30, 0.3)
+            .with_elites(2)
+            .with_seed(0)
+            .evolve(&mut network, 100, &mut fitness);
+        // elites never get worse
+        assert!(all_fitness.windows(2).all(|w| w[1] >= w[0]));
+        assert!(*all_fitness.last().unwrap() > initial);
+        assert_eq!(fitness(&mut network), *all_fitness.last().unwrap());
+    }
+}
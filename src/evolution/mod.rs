@@ -0,0 +1,98 @@
+mod evolution_strategies;
+mod genetic_algorithm;
+
+pub use evolution_strategies::EvolutionStrategies;
+pub use genetic_algorithm::GeneticAlgorithm;
+
+use crate::activators::Activator;
+use crate::network::Network;
+use crate::objectives::Objective;
+use crate::optimizers::Optimizer;
+
+// gradient-free trainers for non-differentiable rewards, which search over the
+// parameters (see Network::parameters) of the layers which aren't frozen with a
+// population of candidates, each evaluated by loading it into the network
+pub trait Evolution {
+    // network: architecture and starting weights, ends with the fittest weights
+    // generations: number of generations to evolve
+    // fitness: higher is better, evaluated on the network with candidate weights
+    // return: fitness of the network's weights after each generation
+    fn evolve<A, Obj, Opt, F>(
+        &mut self,
+        network: &mut Network<A, Obj, Opt>,
+        generations: usize,
+        fitness: F,
+    ) -> Vec<f64>
+    where
+        A: Activator,
+        Obj: Objective<A>,
+        Opt: Optimizer,
+        F: FnMut(&mut Network<A, Obj, Opt>) -> f64;
+}
+
+// load parameters into the network and evaluate its fitness
+fn evaluate<A, Obj, Opt, F>(
+    network: &mut Network<A, Obj, Opt>,
+    parameters: &[f64],
+    fitness: &mut F,
+) -> f64
+where
+    A: Activator,
+    Obj: Objective<A>,
+    Opt: Optimizer,
+    F: FnMut(&mut Network<A, Obj, Opt>) -> f64,
+{
+    network.set_trainable_parameters(parameters);
+    fitness(network)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Evolution, EvolutionStrategies, GeneticAlgorithm};
+    use crate::activators::Sigmoid;
+    use crate::network::Network;
+    use crate::objectives::MeanSquareError;
+    use crate::optimizers::SGD;
+    use crate::NetworkBuilder;
+
+    // a network with a fitness which is highest when it maps 1 to 0.8 and -1 to 0.2
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn network_and_fitness() -> (
+        Network<Sigmoid, MeanSquareError, SGD>,
+        impl FnMut(&mut Network<Sigmoid, MeanSquareError, SGD>) -> f64,
+    ) {
+        let network = NetworkBuilder::new()
+            .input(1)
+            .add_layer_with_weights_and_bias(
+                2,
+                Box::new(Sigmoid),
+                vec![vec![0.], vec![0.]],
+                vec![0., 0.],
+            )
+            .output_with_weights_and_bias(1, vec![vec![0., 0.]], vec![0.])
+            .minimize_to(MeanSquareError::new())
+            .optimize_with(SGD::new(0.1))
+            .build();
+        let fitness = |network: &mut Network<Sigmoid, MeanSquareError, SGD>| {
+            -(network.infer(&[1.])[0] - 0.8).powi(2) - (network.infer(&[-1.])[0] - 0.2).powi(2)
+        };
+        (network, fitness)
+    }
+
+    #[test]
+    fn test_frozen_layers() {
+        fn check<E: Evolution>(mut evolution: E) {
+            let (mut network, fitness) = network_and_fitness();
+            // the output bias is off, and the best output for both inputs is 0.5
+            network.set_parameters(&[0., 0., 0., 0., 0., 0., 2.]);
+            network.set_frozen(0, true);
+            let parameters = network.parameters();
+            evolution.evolve(&mut network, 10, fitness);
+            // the hidden layer's 2 weights and 2 bias are kept
+            assert_eq!(network.parameters()[..4], parameters[..4]);
+            assert_ne!(network.parameters()[4..], parameters[4..]);
+        }
+        check(EvolutionStrategies::new(0.1, 0.05).with_seed(0));
+        check(GeneticAlgorithm::new(10, 0.3).with_seed(0));
+    }
+}
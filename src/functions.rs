@@ -1,5 +1,6 @@
 use rand::{thread_rng, Rng};
 use std::cmp::Ordering;
+use std::f64::consts::PI;
 
 pub fn into_onehot(idx: usize, classes: usize) -> Vec<f64> {
     debug_assert!(idx < classes, "onehot idx must less than classes");
@@ -20,7 +21,7 @@ pub fn argmax(arr: &[f64]) -> usize {
 pub fn softmax(arr: &[f64]) -> Vec<f64> {
     let max = arr
         .iter()
-        .max_by(|a, b| a.partial_cmp(&b).unwrap_or(Ordering::Equal))
+        .max_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal))
         .unwrap();
 
     let exps: Vec<f64> = arr.iter().map(|x| (x - max).exp()).collect();
@@ -28,10 +29,47 @@ pub fn softmax(arr: &[f64]) -> Vec<f64> {
     exps.into_iter().map(|v| v / sum_exp).collect()
 }
 
+// log(softmax(x)) = x - max - ln(SUM(exp(x - max)))
+// never underflows to ln(0) like ln(softmax(x)) does
+pub fn log_softmax(arr: &[f64]) -> Vec<f64> {
+    let max = arr
+        .iter()
+        .max_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+        .unwrap();
+
+    let log_sum_exp = arr.iter().map(|x| (x - max).exp()).sum::<f64>().ln() + max;
+    arr.iter().map(|x| x - log_sum_exp).collect()
+}
+
 pub fn sigmoid(x: f64) -> f64 {
     1. / (1. + (-x).exp())
 }
 
+// log(sigmoid(x)) = -ln(1 + exp(-x)), without overflow for large |x|
+pub fn log_sigmoid(x: f64) -> f64 {
+    -((-x).max(0.) + (-x.abs()).exp().ln_1p())
+}
+
+pub fn euclidean_distance(a: &[f64], b: &[f64]) -> f64 {
+    a.iter()
+        .zip(b.iter())
+        .map(|(a, b)| (a - b).powf(2.))
+        .sum::<f64>()
+        .sqrt()
+}
+
+pub fn cosine_similarity(a: &[f64], b: &[f64]) -> f64 {
+    let dot: f64 = a.iter().zip(b.iter()).map(|(a, b)| a * b).sum();
+    let norm = |v: &[f64]| v.iter().map(|v| v * v).sum::<f64>().sqrt();
+    dot / (norm(a) * norm(b)).max(1e-12)
+}
+
+// sample of N(0, 1) by Box-Muller transform
+pub fn standard_normal<R: Rng + ?Sized>(rng: &mut R) -> f64 {
+    let (u1, u2): (f64, f64) = (1. - rng.gen::<f64>(), rng.gen());
+    (-2. * u1.ln()).sqrt() * (2. * PI * u2).cos()
+}
+
 pub fn transform<F>(
     mut_matrix: &mut [Vec<f64>],
     matrix: &[Vec<f64>],
@@ -108,6 +146,7 @@ where
 // precision : p = tp / (tp + fp)
 // recall    : r = tp / (tp + fn)
 // accuracy  : acc = (tp + tn) / (tp + tn + fp + fn)
+#[allow(clippy::ptr_arg)]
 pub fn print_confusion_matrix(matrix: &Vec<Vec<u32>>) {
     let rows = matrix.len();
     let cols = matrix[0].len();
@@ -121,7 +160,7 @@ pub fn print_confusion_matrix(matrix: &Vec<Vec<u32>>) {
     );
     println!(
         "-|{}",
-        (0..cols).map(|_| format!("\t-")).collect::<String>()
+        (0..cols).map(|_| "\t-".to_string()).collect::<String>()
     );
 
     // precision
@@ -137,7 +176,7 @@ pub fn print_confusion_matrix(matrix: &Vec<Vec<u32>>) {
     );
     println!(
         "-|{}",
-        (0..cols).map(|_| format!("\t-")).collect::<String>()
+        (0..cols).map(|_| "\t-".to_string()).collect::<String>()
     );
 
     // matrix
@@ -154,7 +193,7 @@ pub fn print_confusion_matrix(matrix: &Vec<Vec<u32>>) {
             }
             total_ins += *num;
         }
-        println!("");
+        println!();
     }
 
     // acc
@@ -0,0 +1,101 @@
+// clipping of the mean gradients of a minibatch before the optimizer steps,
+// against exploding gradients on deeper networks
+pub enum GradientClip {
+    // clip every gradient into [-value, value]
+    Value(f64),
+    // rescale each layer's gradients whose L2 norm is over max_norm to max_norm
+    LayerNorm(f64),
+    // rescale all gradients whose L2 norm across all layers is over max_norm to max_norm
+    // https://arxiv.org/abs/1211.5063
+    GlobalNorm(f64),
+}
+
+impl GradientClip {
+    // gradients: all layers' (gradients, bias_gradients)
+    // return: L2 norm of all gradients before clipping
+    pub fn clip(&self, gradients: &mut [(Vec<Vec<f64>>, Vec<f64>)]) -> f64 {
+        let global_norm = global_norm(gradients);
+        match *self {
+            GradientClip::Value(value) => gradients.iter_mut().for_each(|layer| {
+                scale_layer(layer, |g| g.max(-value).min(value));
+            }),
+            GradientClip::LayerNorm(max_norm) => gradients.iter_mut().for_each(|layer| {
+                let norm = layer_norm(layer);
+                if norm > max_norm {
+                    scale_layer(layer, |g| g * max_norm / norm);
+                }
+            }),
+            GradientClip::GlobalNorm(max_norm) => {
+                if global_norm > max_norm {
+                    gradients.iter_mut().for_each(|layer| {
+                        scale_layer(layer, |g| g * max_norm / global_norm);
+                    });
+                }
+            }
+        }
+        global_norm
+    }
+}
+
+// L2 norm of all layers' gradients and bias_gradients
+pub fn global_norm(gradients: &[(Vec<Vec<f64>>, Vec<f64>)]) -> f64 {
+    gradients
+        .iter()
+        .map(|layer| layer_norm(layer).powi(2))
+        .sum::<f64>()
+        .sqrt()
+}
+
+fn layer_norm((gradients, bias_gradients): &(Vec<Vec<f64>>, Vec<f64>)) -> f64 {
+    gradients
+        .iter()
+        .flatten()
+        .chain(bias_gradients.iter())
+        .map(|g| g * g)
+        .sum::<f64>()
+        .sqrt()
+}
+
+fn scale_layer<F>((gradients, bias_gradients): &mut (Vec<Vec<f64>>, Vec<f64>), f: F)
+where
+    F: Fn(f64) -> f64,
+{
+    gradients
+        .iter_mut()
+        .flatten()
+        .chain(bias_gradients.iter_mut())
+        .for_each(|g| *g = f(*g));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gradient_clip() {
+        // layer norms 5 and 12, global norm 13
+        let gradients = vec![(vec![vec![3.]], vec![-4.]), (vec![vec![12.]], vec![0.])];
+
+        let mut clipped = gradients.clone();
+        assert_eq!(GradientClip::Value(3.5).clip(&mut clipped), 13.);
+        assert_eq!(
+            clipped,
+            [(vec![vec![3.]], vec![-3.5]), (vec![vec![3.5]], vec![0.])]
+        );
+
+        let mut clipped = gradients.clone();
+        GradientClip::LayerNorm(10.).clip(&mut clipped);
+        assert_eq!(
+            clipped,
+            [(vec![vec![3.]], vec![-4.]), (vec![vec![10.]], vec![0.])]
+        );
+
+        let mut clipped = gradients;
+        GradientClip::GlobalNorm(6.5).clip(&mut clipped);
+        assert_eq!(
+            clipped,
+            [(vec![vec![1.5]], vec![-2.]), (vec![vec![6.]], vec![0.])]
+        );
+        assert_eq!(global_norm(&clipped), 6.5);
+    }
+}
@@ -0,0 +1,214 @@
+// why fit stopped
+#[derive(Debug, PartialEq)]
+pub enum StopReason {
+    // ran all epochs
+    Completed,
+    // Network::stop_training was called, eg. by EarlyStopping
+    Stopped(String),
+}
+
+// metrics of one minibatch
+pub struct BatchRecord {
+    // epochs done before this one over all fits
+    pub epoch: usize,
+    // index of the minibatch within the epoch
+    pub batch: usize,
+    // weighted mean loss
+    pub loss: f64,
+    pub accuracy: f64,
+    // optimizer's learning rate the minibatch was fit with
+    pub learning_rate: f64,
+    // L2 norm of the mean gradients before clipping, see Network::gradient_norm
+    pub gradient_norm: f64,
+    // wall-clock time of forward, backward and optimizer step
+    pub seconds: f64,
+}
+
+// metrics of one epoch, and of the validation data evaluated after it, if any
+pub struct EpochRecord {
+    pub epoch: usize,
+    pub loss: f64,
+    pub accuracy: f64,
+    pub val_loss: Option<f64>,
+    pub val_accuracy: Option<f64>,
+    // optimizer's learning rate at the end of the epoch, before the scheduler's on_epoch
+    pub learning_rate: f64,
+    // wall-clock time of the epoch including validation
+    pub seconds: f64,
+}
+
+// what fit went through, returned by Network::fit
+pub struct TrainingHistory {
+    pub batches: Vec<BatchRecord>,
+    pub epochs: Vec<EpochRecord>,
+    pub stop_reason: StopReason,
+}
+
+impl TrainingHistory {
+    pub(crate) fn new() -> TrainingHistory {
+        TrainingHistory {
+            batches: vec![],
+            epochs: vec![],
+            stop_reason: StopReason::Completed,
+        }
+    }
+
+    pub fn batch_losses(&self) -> Vec<f64> {
+        self.batches.iter().map(|batch| batch.loss).collect()
+    }
+
+    // wall-clock time of all epochs
+    pub fn seconds(&self) -> f64 {
+        self.epochs.iter().map(|epoch| epoch.seconds).sum()
+    }
+
+    // one row per epoch with header, missing validation metrics are empty
+    pub fn epochs_to_csv(&self) -> String {
+        let mut csv =
+            String::from("epoch,loss,accuracy,val_loss,val_accuracy,learning_rate,seconds\n");
+        for epoch in &self.epochs {
+            let optional = |value: Option<f64>| value.map_or(String::new(), |v| v.to_string());
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{}\n",
+                epoch.epoch,
+                epoch.loss,
+                epoch.accuracy,
+                optional(epoch.val_loss),
+                optional(epoch.val_accuracy),
+                epoch.learning_rate,
+                epoch.seconds,
+            ));
+        }
+        csv
+    }
+
+    // one row per minibatch with header
+    pub fn batches_to_csv(&self) -> String {
+        let mut csv =
+            String::from("epoch,batch,loss,accuracy,learning_rate,gradient_norm,seconds\n");
+        for batch in &self.batches {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{}\n",
+                batch.epoch,
+                batch.batch,
+                batch.loss,
+                batch.accuracy,
+                batch.learning_rate,
+                batch.gradient_norm,
+                batch.seconds,
+            ));
+        }
+        csv
+    }
+
+    // {"stop_reason": .., "epochs": [{..}], "batches": [{..}]}
+    // missing and non-finite numbers are null
+    pub fn to_json(&self) -> String {
+        let epochs: Vec<String> = self
+            .epochs
+            .iter()
+            .map(|epoch| {
+                format!(
+                    r#"{{"epoch":{},"loss":{},"accuracy":{},"val_loss":{},"val_accuracy":{},"learning_rate":{},"seconds":{}}}"#,
+                    epoch.epoch,
+                    json_number(Some(epoch.loss)),
+                    json_number(Some(epoch.accuracy)),
+                    json_number(epoch.val_loss),
+                    json_number(epoch.val_accuracy),
+                    json_number(Some(epoch.learning_rate)),
+                    json_number(Some(epoch.seconds)),
+                )
+            })
+            .collect();
+        let batches: Vec<String> = self
+            .batches
+            .iter()
+            .map(|batch| {
+                format!(
+                    r#"{{"epoch":{},"batch":{},"loss":{},"accuracy":{},"learning_rate":{},"gradient_norm":{},"seconds":{}}}"#,
+                    batch.epoch,
+                    batch.batch,
+                    json_number(Some(batch.loss)),
+                    json_number(Some(batch.accuracy)),
+                    json_number(Some(batch.learning_rate)),
+                    json_number(Some(batch.gradient_norm)),
+                    json_number(Some(batch.seconds)),
+                )
+            })
+            .collect();
+        let stop_reason = match &self.stop_reason {
+            StopReason::Completed => "completed",
+            StopReason::Stopped(reason) => reason,
+        };
+        format!(
+            r#"{{"stop_reason":{},"epochs":[{}],"batches":[{}]}}"#,
+            json_string(stop_reason),
+            epochs.join(","),
+            batches.join(","),
+        )
+    }
+}
+
+fn json_number(value: Option<f64>) -> String {
+    match value {
+        Some(value) if value.is_finite() => value.to_string(),
+        _ => String::from("null"),
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut json = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export() {
+        let history = TrainingHistory {
+            batches: vec![BatchRecord {
+                epoch: 0,
+                batch: 0,
+                loss: 0.5,
+                accuracy: 1.,
+                learning_rate: 0.1,
+                gradient_norm: 2.,
+                seconds: 0.25,
+            }],
+            epochs: vec![EpochRecord {
+                epoch: 0,
+                loss: 0.5,
+                accuracy: 1.,
+                val_loss: None,
+                val_accuracy: Some(0.75),
+                learning_rate: 0.1,
+                seconds: f64::NAN,
+            }],
+            stop_reason: StopReason::Stopped(String::from("\"early\" stopping")),
+        };
+        assert_eq!(
+            history.epochs_to_csv(),
+            "epoch,loss,accuracy,val_loss,val_accuracy,learning_rate,seconds\n0,0.5,1,,0.75,0.1,NaN\n"
+        );
+        assert_eq!(
+            history.batches_to_csv(),
+            "epoch,batch,loss,accuracy,learning_rate,gradient_norm,seconds\n0,0,0.5,1,0.1,2,0.25\n"
+        );
+        assert_eq!(
+            history.to_json(),
+            r#"{"stop_reason":"\"early\" stopping","epochs":[{"epoch":0,"loss":0.5,"accuracy":1,"val_loss":null,"val_accuracy":0.75,"learning_rate":0.1,"seconds":null}],"batches":[{"epoch":0,"batch":0,"loss":0.5,"accuracy":1,"learning_rate":0.1,"gradient_norm":2,"seconds":0.25}]}"#
+        );
+    }
+}
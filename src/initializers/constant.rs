@@ -0,0 +1,43 @@
+use super::Initializer;
+
+use rand::RngCore;
+
+// every value is the same, eg. a small positive bias for ReLU layers
+#[derive(Debug)]
+pub struct Constant {
+    value: f64,
+}
+
+impl Constant {
+    pub fn new(value: f64) -> Constant {
+        Constant { value }
+    }
+}
+
+impl Initializer for Constant {
+    fn init(
+        &self,
+        (rows, cols): (usize, usize),
+        _: usize,
+        _: usize,
+        _: &mut dyn RngCore,
+    ) -> Vec<Vec<f64>> {
+        vec![vec![self.value; cols]; rows]
+    }
+}
+
+// the default for bias
+#[derive(Debug)]
+pub struct Zeros;
+
+impl Initializer for Zeros {
+    fn init(
+        &self,
+        shape: (usize, usize),
+        fan_in: usize,
+        fan_out: usize,
+        rng: &mut dyn RngCore,
+    ) -> Vec<Vec<f64>> {
+        Constant::new(0.).init(shape, fan_in, fan_out, rng)
+    }
+}
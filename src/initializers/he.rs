@@ -0,0 +1,58 @@
+use super::{variance_scaling, Distribution, FanMode, Initializer};
+
+use rand::RngCore;
+
+// https://arxiv.org/abs/1502.01852
+//
+// for f(x) = ReLU(x), variance = 2 / fan
+#[derive(Debug)]
+pub struct He {
+    distribution: Distribution,
+    mode: FanMode,
+}
+
+impl Default for He {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl He {
+    // normal over fan_in
+    pub fn new() -> He {
+        He {
+            distribution: Distribution::Normal,
+            mode: FanMode::FanIn,
+        }
+    }
+
+    pub fn with_distribution(mut self, distribution: Distribution) -> He {
+        self.distribution = distribution;
+        self
+    }
+
+    pub fn with_mode(mut self, mode: FanMode) -> He {
+        self.mode = mode;
+        self
+    }
+}
+
+impl Initializer for He {
+    fn init(
+        &self,
+        shape: (usize, usize),
+        fan_in: usize,
+        fan_out: usize,
+        rng: &mut dyn RngCore,
+    ) -> Vec<Vec<f64>> {
+        variance_scaling(
+            2.,
+            self.mode,
+            self.distribution,
+            shape,
+            fan_in,
+            fan_out,
+            rng,
+        )
+    }
+}
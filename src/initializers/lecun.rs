@@ -0,0 +1,51 @@
+use super::{variance_scaling, Distribution, FanMode, Initializer};
+
+use rand::RngCore;
+
+// http://yann.lecun.com/exdb/publis/pdf/lecun-98b.pdf
+//
+// for f(x) = SELU(x), variance = 1 / fan_in
+#[derive(Debug)]
+pub struct LeCun {
+    distribution: Distribution,
+}
+
+impl Default for LeCun {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LeCun {
+    // normal
+    pub fn new() -> LeCun {
+        LeCun {
+            distribution: Distribution::Normal,
+        }
+    }
+
+    pub fn with_distribution(mut self, distribution: Distribution) -> LeCun {
+        self.distribution = distribution;
+        self
+    }
+}
+
+impl Initializer for LeCun {
+    fn init(
+        &self,
+        shape: (usize, usize),
+        fan_in: usize,
+        fan_out: usize,
+        rng: &mut dyn RngCore,
+    ) -> Vec<Vec<f64>> {
+        variance_scaling(
+            1.,
+            FanMode::FanIn,
+            self.distribution,
+            shape,
+            fan_in,
+            fan_out,
+            rng,
+        )
+    }
+}
@@ -0,0 +1,107 @@
+mod constant;
+mod he;
+mod lecun;
+mod orthogonal;
+mod truncated_normal;
+mod xavier;
+
+pub use constant::{Constant, Zeros};
+pub use he::He;
+pub use lecun::LeCun;
+pub use orthogonal::Orthogonal;
+pub use truncated_normal::TruncatedNormal;
+pub use xavier::Xavier;
+
+use crate::functions::standard_normal;
+
+use rand::{Rng, RngCore};
+use std::fmt::Debug;
+
+// initial values of a layer's weights or bias, drawn from the network builder's
+// rng, or any other, eg. a seeded one
+pub trait Initializer: Debug {
+    // shape: (rows, cols) of the values, (num_nodes, input_dim) for weights
+    //        and (1, num_nodes) for bias
+    // fan_in, fan_out: inputs and nodes of the layer
+    fn init(
+        &self,
+        shape: (usize, usize),
+        fan_in: usize,
+        fan_out: usize,
+        rng: &mut dyn RngCore,
+    ) -> Vec<Vec<f64>>;
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Distribution {
+    // N(0, stddev)
+    Normal,
+    // U(-limit, limit) with the same variance
+    Uniform,
+}
+
+// which fan scales the variance
+#[derive(Debug, Clone, Copy)]
+pub enum FanMode {
+    // keeps the variance of the outputs in the forward pass
+    FanIn,
+    // keeps the variance of the deltas in the backward pass
+    FanOut,
+    // compromise of both, (fan_in + fan_out) / 2
+    FanAvg,
+}
+
+// values with variance = scale / fan
+fn variance_scaling(
+    scale: f64,
+    mode: FanMode,
+    distribution: Distribution,
+    (rows, cols): (usize, usize),
+    fan_in: usize,
+    fan_out: usize,
+    rng: &mut dyn RngCore,
+) -> Vec<Vec<f64>> {
+    let fan = match mode {
+        FanMode::FanIn => fan_in as f64,
+        FanMode::FanOut => fan_out as f64,
+        FanMode::FanAvg => (fan_in + fan_out) as f64 / 2.,
+    };
+    let variance = scale / fan.max(1.);
+    (0..rows)
+        .map(|_| {
+            (0..cols)
+                .map(|_| match distribution {
+                    Distribution::Normal => variance.sqrt() * standard_normal(rng),
+                    Distribution::Uniform => {
+                        let limit = (3. * variance).sqrt();
+                        rng.gen_range(-limit, limit)
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_variance_scaling() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let shape = (100, 200);
+        let variance = |values: &[Vec<f64>]| {
+            values.iter().flatten().map(|v| v * v).sum::<f64>() / (shape.0 * shape.1) as f64
+        };
+
+        let he = He::new().init(shape, 200, 100, &mut rng);
+        assert!((variance(&he) - 0.01).abs() < 0.001);
+
+        let xavier = Xavier::new().init(shape, 200, 100, &mut rng);
+        let limit = (6. / 300_f64).sqrt();
+        assert!(xavier.iter().flatten().all(|v| v.abs() <= limit));
+        assert!((variance(&xavier) - 1. / 150.).abs() < 0.001);
+    }
+}
@@ -0,0 +1,100 @@
+use super::Initializer;
+use crate::functions::standard_normal;
+
+use rand::RngCore;
+
+// https://arxiv.org/abs/1312.6120
+//
+// rows (or columns, whichever are fewer) are orthonormal, times gain,
+// by Gram-Schmidt over a random normal matrix
+#[derive(Debug)]
+pub struct Orthogonal {
+    gain: f64,
+}
+
+impl Default for Orthogonal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Orthogonal {
+    pub fn new() -> Orthogonal {
+        Orthogonal { gain: 1. }
+    }
+
+    pub fn with_gain(mut self, gain: f64) -> Orthogonal {
+        self.gain = gain;
+        self
+    }
+}
+
+impl Initializer for Orthogonal {
+    fn init(
+        &self,
+        (rows, cols): (usize, usize),
+        _: usize,
+        _: usize,
+        rng: &mut dyn RngCore,
+    ) -> Vec<Vec<f64>> {
+        // the fewer vectors of the longer dimension can be orthonormal
+        let (num_vectors, dim) = (rows.min(cols), rows.max(cols));
+        let mut vectors: Vec<Vec<f64>> = vec![];
+        while vectors.len() < num_vectors {
+            let mut v: Vec<f64> = (0..dim).map(|_| standard_normal(rng)).collect();
+            for u in &vectors {
+                let dot: f64 = v.iter().zip(u.iter()).map(|(v, u)| v * u).sum();
+                v.iter_mut().zip(u.iter()).for_each(|(v, u)| *v -= dot * u);
+            }
+            let norm = v.iter().map(|v| v * v).sum::<f64>().sqrt();
+            // redraw the rare vector that is almost dependent on the others
+            if norm > 1e-6 {
+                vectors.push(v.iter().map(|v| v / norm).collect());
+            }
+        }
+
+        let gain = self.gain;
+        if rows <= cols {
+            vectors
+                .into_iter()
+                .map(|row| row.into_iter().map(|v| gain * v).collect())
+                .collect()
+        } else {
+            (0..rows)
+                .map(|i| vectors.iter().map(|column| gain * column[i]).collect())
+                .collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_orthogonal() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for &shape in &[(3, 5), (5, 3)] {
+            let weights = Orthogonal::new().with_gain(2.).init(shape, 5, 3, &mut rng);
+            assert_eq!((weights.len(), weights[0].len()), shape);
+            // W * W^T (or W^T * W) = gain^2 * I
+            let n = shape.0.min(shape.1);
+            let vector = |i: usize| -> Vec<f64> {
+                if shape.0 <= shape.1 {
+                    weights[i].clone()
+                } else {
+                    weights.iter().map(|row| row[i]).collect()
+                }
+            };
+            for i in 0..n {
+                for j in 0..n {
+                    let dot: f64 = vector(i).iter().zip(vector(j)).map(|(a, b)| a * b).sum();
+                    let expected = if i == j { 4. } else { 0. };
+                    assert!((dot - expected).abs() < 1e-9);
+                }
+            }
+        }
+    }
+}
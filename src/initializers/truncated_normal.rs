@@ -0,0 +1,41 @@
+use super::Initializer;
+use crate::functions::standard_normal;
+
+use rand::RngCore;
+
+// N(mean, stddev) with the values more than 2 stddev from the mean redrawn
+#[derive(Debug)]
+pub struct TruncatedNormal {
+    mean: f64,
+    stddev: f64,
+}
+
+impl TruncatedNormal {
+    pub fn new(mean: f64, stddev: f64) -> TruncatedNormal {
+        assert!(stddev > 0., "stddev should be positive");
+        TruncatedNormal { mean, stddev }
+    }
+}
+
+impl Initializer for TruncatedNormal {
+    fn init(
+        &self,
+        (rows, cols): (usize, usize),
+        _: usize,
+        _: usize,
+        rng: &mut dyn RngCore,
+    ) -> Vec<Vec<f64>> {
+        (0..rows)
+            .map(|_| {
+                (0..cols)
+                    .map(|_| loop {
+                        let z = standard_normal(rng);
+                        if z.abs() <= 2. {
+                            break self.mean + self.stddev * z;
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
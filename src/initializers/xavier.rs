@@ -0,0 +1,51 @@
+use super::{variance_scaling, Distribution, FanMode, Initializer};
+
+use rand::RngCore;
+
+// http://proceedings.mlr.press/v9/glorot10a/glorot10a.pdf
+//
+// for f(x) = x or f(x) = tanh(x), variance = 2 / (fan_in + fan_out)
+#[derive(Debug)]
+pub struct Xavier {
+    distribution: Distribution,
+}
+
+impl Default for Xavier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Xavier {
+    // uniform
+    pub fn new() -> Xavier {
+        Xavier {
+            distribution: Distribution::Uniform,
+        }
+    }
+
+    pub fn with_distribution(mut self, distribution: Distribution) -> Xavier {
+        self.distribution = distribution;
+        self
+    }
+}
+
+impl Initializer for Xavier {
+    fn init(
+        &self,
+        shape: (usize, usize),
+        fan_in: usize,
+        fan_out: usize,
+        rng: &mut dyn RngCore,
+    ) -> Vec<Vec<f64>> {
+        variance_scaling(
+            1.,
+            FanMode::FanAvg,
+            self.distribution,
+            shape,
+            fan_in,
+            fan_out,
+            rng,
+        )
+    }
+}
@@ -1,17 +1,30 @@
 use crate::activators::Activator;
-use crate::functions::he_init;
+use crate::constraints::Constraint;
+use crate::initializers::{He, Initializer};
+use crate::regularizers::Regularizer;
+
+use rand::thread_rng;
 
 #[derive(Debug)]
 pub struct Layer {
     pub weights: Vec<Vec<f64>>,
     pub bias: Vec<f64>, // the weight of bias, assume bias always be 1.
     pub activator: Box<dyn Activator>,
+    pub regularizer: Option<Box<dyn Regularizer>>,
+    pub constraint: Option<Box<dyn Constraint>>,
+    // frozen layers keep their weights and bias, eg. pretrained layers
+    pub frozen: bool,
+    // pass deltas to the previous layers, otherwise they get no gradients
+    pub propagate_deltas: bool,
+    // scales the optimizer's learning rate for this layer
+    pub lr_multiplier: f64,
 }
 
 impl Layer {
     // Used as a public API for construction and validation of layers in a network
     // when `None` is specified, the most common defaults are used
     // IE He initialization for seed weights, 0 for bias
+    // the default weights are drawn by initializers::He like NetworkBuilder::add_layer
     pub fn new(
         input_dim: usize,
         num_nodes: usize,
@@ -19,7 +32,14 @@ impl Layer {
         seed_weights: Option<Vec<Vec<f64>>>,
         seed_bias: Option<Vec<f64>>,
     ) -> Self {
-        let weights = seed_weights.unwrap_or_else(|| he_init(input_dim, num_nodes));
+        let weights = seed_weights.unwrap_or_else(|| {
+            He::new().init(
+                (num_nodes, input_dim),
+                input_dim,
+                num_nodes,
+                &mut thread_rng(),
+            )
+        });
         assert_eq!(weights.len(), num_nodes);
         for input_weights in &weights {
             assert_eq!(input_weights.len(), input_dim);
@@ -33,6 +53,11 @@ impl Layer {
             bias,
             weights,
             activator,
+            regularizer: None,
+            constraint: None,
+            frozen: false,
+            propagate_deltas: true,
+            lr_multiplier: 1.,
         }
     }
 
@@ -74,6 +99,7 @@ impl Layer {
     // curr_output: minibatch of current layer's output
     // prev_output: minibatch of previous layer's output
     // return: minibatch of previous layer's delta_without_deriv and current layer's gradients
+    #[allow(clippy::type_complexity)]
     pub fn delta_without_deriv_and_gradient(
         &self,
         curr_delta_without_derivs: &[Vec<f64>],
@@ -87,6 +113,20 @@ impl Layer {
         (prev_delta_without_derivs, gradients, bias_gradients)
     }
 
+    // same as delta_without_deriv_and_gradient without the gradients, for frozen layers
+    //
+    // curr_delta_without_deriv: minibatch of current layer's delta_without_deriv
+    // curr_output: minibatch of current layer's output
+    // return: minibatch of previous layer's delta_without_deriv
+    pub fn delta_without_deriv(
+        &self,
+        curr_delta_without_derivs: &[Vec<f64>],
+        curr_outputs: &[Vec<f64>],
+    ) -> Vec<Vec<f64>> {
+        let curr_deltas = self.delta(curr_delta_without_derivs, curr_outputs);
+        self.prev_delta_without_deriv(&curr_deltas)
+    }
+
     // curr_delta_without_deriv: minibatch of current layer delta_without_deriv
     // curr_output: minibatch of current layer output
     // return: minibatch of current layer's delta
@@ -138,7 +178,7 @@ impl Layer {
             .collect();
 
         // bias gradient = curr_delta * prev_output = curr_delta * 1
-        let bias_gradients = curr_deltas.iter().cloned().collect();
+        let bias_gradients = curr_deltas.to_vec();
 
         (gradients, bias_gradients)
     }
@@ -1,9 +1,19 @@
 pub mod activators;
+pub mod callbacks;
+pub mod constraints;
+pub mod evolution;
 pub mod functions;
+pub mod gradient_clip;
+pub mod history;
+pub mod initializers;
 pub mod layers;
 pub mod network;
 pub mod network_builder;
 pub mod objectives;
 pub mod optimizers;
+pub mod regularizers;
+pub mod schedulers;
+pub mod validation;
+pub mod weight_averaging;
 
 pub use network_builder::*;
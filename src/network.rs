@@ -1,194 +1,912 @@
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
-use rand::thread_rng;
+use rand::SeedableRng;
 use std::marker::PhantomData;
-use textplots::{Chart, Plot, Shape};
+use std::mem;
+use std::time::Instant;
 
 use crate::activators::Activator;
+use crate::callbacks::{BatchLogs, Callback, EpochLogs, FitParams};
 use crate::functions::{transform_matrix, transform_vec};
+use crate::gradient_clip::{global_norm, GradientClip};
+use crate::history::{BatchRecord, EpochRecord, StopReason, TrainingHistory};
 use crate::layers::Layer;
 use crate::objectives::Objective;
-use crate::optimizers::Optimizer;
+use crate::optimizers::{ClosureOptimizer, Optimizer};
+use crate::schedulers::LrScheduler;
+use crate::validation::Validation;
+use crate::weight_averaging::{AveragedWeights, WeightAveraging};
+
+// one training example, kept together while shuffling
+struct Sample {
+    input: Vec<f64>,
+    expected: Vec<f64>,
+    weight: f64,
+    mask: Option<Vec<f64>>,
+}
 
 pub struct Network<A: Activator, Obj: Objective<A>, Opt: Optimizer> {
+    #[allow(clippy::vec_box)]
     layers: Vec<Box<Layer>>,
     objective: Obj,
     optimizer: Opt,
+    lr_scheduler: Option<Box<dyn LrScheduler>>,
+    gradient_clip: Option<GradientClip>,
+    // L2 norm of the last minibatch's mean gradients before clipping
+    gradient_norm: f64,
+    // optimizer's learning rate when the scheduler was set
+    base_learning_rate: f64,
+    // optimizer steps once every accumulation_steps minibatches
+    accumulation_steps: usize,
+    // summed gradients and sample weights since the last optimizer step
+    accumulated_gradients: Vec<(Vec<Vec<f64>>, Vec<f64>)>,
+    accumulated_weight: f64,
+    averaged_weights: Option<AveragedWeights>,
+    callbacks: Vec<Box<dyn Callback<A, Obj, Opt>>>,
+    validation: Option<Validation>,
+    // reason set by stop_training, fit stops after the current epoch
+    stop_training: Option<String>,
+    // shuffles the samples of fit
+    rng: StdRng,
+    // optimizer steps and epochs taken over all fits
+    steps: usize,
+    epochs: usize,
     _marker: PhantomData<A>,
 }
 
 impl<A: Activator, Obj: Objective<A>, Opt: Optimizer> Network<A, Obj, Opt> {
     pub fn new(layers: Vec<Box<Layer>>, objective: Obj, optimizer: Opt) -> Self {
+        let base_learning_rate = optimizer.learning_rate();
         Network {
             layers,
             objective,
             optimizer,
+            lr_scheduler: None,
+            gradient_clip: None,
+            gradient_norm: 0.,
+            accumulation_steps: 1,
+            accumulated_gradients: vec![],
+            accumulated_weight: 0.,
+            averaged_weights: None,
+            callbacks: vec![],
+            validation: None,
+            stop_training: None,
+            rng: StdRng::from_entropy(),
+            base_learning_rate,
+            steps: 0,
+            epochs: 0,
             _marker: PhantomData,
         }
     }
 
+    // called back in order during fit
+    pub fn add_callback(&mut self, callback: Box<dyn Callback<A, Obj, Opt>>) {
+        self.callbacks.push(callback);
+    }
+
+    // stop fit after the current epoch, eg. from a callback
+    // reason: the stop reason of the returned history
+    pub fn stop_training(&mut self, reason: &str) {
+        self.stop_training = Some(String::from(reason));
+    }
+
+    pub fn optimizer(&self) -> &Opt {
+        &self.optimizer
+    }
+
+    pub fn optimizer_mut(&mut self) -> &mut Opt {
+        &mut self.optimizer
+    }
+
+    // reproducible order of minibatches, see also NetworkBuilder::with_seed
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
+    pub fn set_rng(&mut self, rng: StdRng) {
+        self.rng = rng;
+    }
+
+    // vary the optimizer's learning rate over training, starting from its
+    // current learning rate
+    pub fn set_lr_scheduler(&mut self, lr_scheduler: Box<dyn LrScheduler>) {
+        self.base_learning_rate = self.optimizer.learning_rate();
+        self.lr_scheduler = Some(lr_scheduler);
+    }
+
+    pub fn set_gradient_clip(&mut self, gradient_clip: Option<GradientClip>) {
+        self.gradient_clip = gradient_clip;
+    }
+
+    // sum gradients over accumulation_steps minibatches before each optimizer
+    // step, the same update as one accumulation_steps times larger minibatch
+    // with less memory, except for objectives pairing samples within a minibatch
+    // the last step of an epoch may have fewer minibatches
+    pub fn set_gradient_accumulation(&mut self, accumulation_steps: usize) {
+        assert!(
+            accumulation_steps > 0,
+            "accumulation steps should be positive"
+        );
+        self.accumulation_steps = accumulation_steps;
+    }
+
+    // L2 norm of all layers' mean gradients of the last optimizer step before
+    // clipping, to monitor for exploding gradients
+    pub fn gradient_norm(&self) -> f64 {
+        self.gradient_norm
+    }
+
+    // keep averaged weights over training, which infer_averaged uses
+    // restarts the average, None stops averaging
+    pub fn set_weight_averaging(&mut self, weight_averaging: Option<WeightAveraging>) {
+        self.averaged_weights = weight_averaging.map(AveragedWeights::new);
+    }
+
+    // evaluate validation data at the end of every epoch of fit, the metrics are
+    // in the returned history and the callbacks' EpochLogs
+    // with weight averaging, validation uses the averaged weights like infer_averaged
+    // schedulers' on_epoch get the validation loss instead of the training loss
+    pub fn set_validation(&mut self, validation: Option<Validation>) {
+        self.validation = validation;
+    }
+
+    // frozen layers keep their weights and bias, can be changed between fits,
+    // eg. unfreeze pretrained layers after the new layers have settled
+    pub fn set_frozen(&mut self, idx: usize, frozen: bool) {
+        self.layers[idx].frozen = frozen;
+    }
+
+    // whether layer idx passes deltas to the previous layers
+    pub fn set_propagate_deltas(&mut self, idx: usize, propagate_deltas: bool) {
+        self.layers[idx].propagate_deltas = propagate_deltas;
+    }
+
+    // scales the optimizer's learning rate for layer idx
+    pub fn set_lr_multiplier(&mut self, idx: usize, lr_multiplier: f64) {
+        self.layers[idx].lr_multiplier = lr_multiplier;
+    }
+
     // fit the network, adjust all weights within the network to account for
     // the way that the error after an Example propogates with the weights.
-    // return the error value BEFORE this round of training.
+    // return the error values BEFORE each minibatch of training, the metrics
+    // of each epoch, and why it stopped
     pub fn fit(
         &mut self,
         inputs: Vec<Vec<f64>>,
         expecteds: Vec<Vec<f64>>,
         epochs: usize,
         batch_size: usize,
-    ) -> Vec<f64> {
+    ) -> TrainingHistory {
+        self.fit_weighted(inputs, expecteds, None, None, epochs, batch_size)
+    }
+
+    // fit with optional per-sample weights (default 1) to reweight rare examples,
+    // and per-output masks (0 for missing targets) for partially-labelled data
+    // batch losses and gradients are weighted means over the minibatch
+    pub fn fit_weighted(
+        &mut self,
+        inputs: Vec<Vec<f64>>,
+        expecteds: Vec<Vec<f64>>,
+        weights: Option<Vec<f64>>,
+        masks: Option<Vec<Vec<f64>>>,
+        epochs: usize,
+        batch_size: usize,
+    ) -> TrainingHistory {
         debug_assert_eq!(inputs[0].len(), self.layers[0].weights[0].len());
-        debug_assert_eq!(
-            expecteds[0].len(),
-            self.layers.last().unwrap().weights.len()
+        // labels are checked by the objective, as some accept sparse labels
+        debug_assert!(
+            self.objective
+                .is_valid_label(&expecteds[0], self.layers.last().unwrap().weights.len()),
+            "label doesn't fit the output layer"
         );
-        let mut all_batch_mean_loss = vec![];
-        let mut train_pairs: Vec<(Vec<f64>, Vec<f64>)> =
-            inputs.into_iter().zip(expecteds.into_iter()).collect();
+        assert_eq!(inputs.len(), expecteds.len());
+        let num_samples = inputs.len();
+        let weights = weights.unwrap_or_else(|| vec![1.; num_samples]);
+        assert_eq!(weights.len(), num_samples, "one weight per sample");
+        let masks: Vec<Option<Vec<f64>>> = match masks {
+            Some(masks) => {
+                assert_eq!(masks.len(), num_samples, "one mask per sample");
+                masks.into_iter().map(Some).collect()
+            }
+            None => vec![None; num_samples],
+        };
+
+        let mut history = TrainingHistory::new();
+        let mut samples: Vec<Sample> = inputs
+            .into_iter()
+            .zip(expecteds)
+            .zip(weights)
+            .zip(masks)
+            .map(|(((input, expected), weight), mask)| Sample {
+                input,
+                expected,
+                weight,
+                mask,
+            })
+            .collect();
+        let validation = self.take_validation(&mut samples);
+        let params = FitParams {
+            epochs,
+            batch_size,
+            num_samples: samples.len(),
+            num_batches: samples.len().div_ceil(batch_size),
+        };
+        // callbacks get the network mutably, so they are taken out while fitting
+        let mut callbacks = mem::take(&mut self.callbacks);
+        self.stop_training = None;
+        callbacks
+            .iter_mut()
+            .for_each(|callback| callback.on_train_begin(self, &params));
         for i in 0..epochs {
+            let epoch = self.epochs;
+            let epoch_start = Instant::now();
+            callbacks
+                .iter_mut()
+                .for_each(|callback| callback.on_epoch_begin(self, epoch));
             // for train data and labels shuffle
-            train_pairs.shuffle(&mut thread_rng());
-            train_pairs.chunks(batch_size).enumerate().fold(
-                (0, 0, 0.),
-                |(total_hit, total_miss, total_loss), (j, train_pairs)| {
-                    let (hit, miss, loss) = self.fit_one_batch(train_pairs);
+            samples.shuffle(&mut self.rng);
+            let (epoch_hit, epoch_miss, epoch_loss, epoch_weight) = samples.chunks(batch_size).enumerate().fold(
+                (0, 0, 0., 0.),
+                |(total_hit, total_miss, total_loss, total_weight), (j, samples)| {
+                    // the first minibatch of an optimizer step
+                    if j % self.accumulation_steps == 0 {
+                        if let Some(lr_scheduler) = self.lr_scheduler.as_mut() {
+                            if let Some(learning_rate) = lr_scheduler.on_step(
+                                self.steps,
+                                self.base_learning_rate,
+                                self.optimizer.learning_rate(),
+                            ) {
+                                self.optimizer.set_learning_rate(learning_rate);
+                            }
+                        }
+                    }
+                    callbacks
+                        .iter_mut()
+                        .for_each(|callback| callback.on_batch_begin(self, epoch, j));
+                    let batch_start = Instant::now();
+                    let (hit, miss, loss, weight) = self.fit_one_batch(samples);
+                    // the last minibatch of an optimizer step, or of the epoch
+                    if (j + 1) % self.accumulation_steps == 0 || j + 1 == params.num_batches {
+                        self.step();
+                    }
 
-                    let num_pairs = hit + miss;
-                    let total_num = (total_hit + total_miss + num_pairs) as f64;
+                    let num_samples = hit + miss;
+                    let total_num = (total_hit + total_miss + num_samples) as f64;
 
-                    let batch_mean_loss = loss / num_pairs as f64;
-                    all_batch_mean_loss.push(batch_mean_loss);
+                    let batch_mean_loss = weighted_mean(loss, weight);
+                    history.batches.push(BatchRecord {
+                        epoch,
+                        batch: j,
+                        loss: batch_mean_loss,
+                        accuracy: hit as f64 / num_samples as f64,
+                        learning_rate: self.optimizer.learning_rate(),
+                        gradient_norm: self.gradient_norm,
+                        seconds: batch_start.elapsed().as_secs_f64(),
+                    });
 
                     log::info!(
-                        "epoch:[{}, acc:{:.3}, loss:{:.3}], batch:[{}-{}, acc:{:.3} loss:{:.3}]",
+                        "epoch:[{}, acc:{:.3}, loss:{:.3}], batch:[{}-{}, acc:{:.3} loss:{:.3}], lr:{:.3e}, grad_norm:{:.3e}",
                         i,
-                        (total_hit + hit) as f64 / total_num as f64,
-                        (total_loss + loss) / total_num as f64,
+                        (total_hit + hit) as f64 / total_num,
+                        weighted_mean(total_loss + loss, total_weight + weight),
                         j * batch_size,
-                        j * batch_size + num_pairs - 1,
-                        hit as f64 / num_pairs as f64,
+                        j * batch_size + num_samples - 1,
+                        hit as f64 / num_samples as f64,
                         batch_mean_loss,
+                        self.optimizer.learning_rate(),
+                        self.gradient_norm,
                     );
-                    (total_hit + hit, total_miss + miss, total_loss + loss)
+                    let logs = BatchLogs {
+                        loss: batch_mean_loss,
+                        accuracy: hit as f64 / num_samples as f64,
+                        num_samples,
+                        gradient_norm: self.gradient_norm,
+                    };
+                    callbacks
+                        .iter_mut()
+                        .for_each(|callback| callback.on_batch_end(self, epoch, j, &logs));
+                    (
+                        total_hit + hit,
+                        total_miss + miss,
+                        total_loss + loss,
+                        total_weight + weight,
+                    )
                 },
             );
+
+            self.epochs += 1;
+            if let Some(averaged_weights) = self.averaged_weights.as_mut() {
+                averaged_weights.on_epoch(self.epochs, &self.layers);
+            }
+            let epoch_mean_loss = weighted_mean(epoch_loss, epoch_weight);
+            let (val_loss, val_accuracy) = match &validation {
+                Some((inputs, expecteds)) => {
+                    let (loss, accuracy) = self.evaluate_averaged(inputs, expecteds, batch_size);
+                    log::info!(
+                        "epoch:[{}, val_acc:{:.3}, val_loss:{:.3}]",
+                        i,
+                        accuracy,
+                        loss
+                    );
+                    (Some(loss), Some(accuracy))
+                }
+                None => (None, None),
+            };
+            let learning_rate = self.optimizer.learning_rate();
+            if let Some(lr_scheduler) = self.lr_scheduler.as_mut() {
+                if let Some(learning_rate) = lr_scheduler.on_epoch(
+                    self.epochs,
+                    self.base_learning_rate,
+                    self.optimizer.learning_rate(),
+                    val_loss.unwrap_or(epoch_mean_loss),
+                ) {
+                    self.optimizer.set_learning_rate(learning_rate);
+                }
+            }
+
+            let logs = EpochLogs {
+                loss: epoch_mean_loss,
+                accuracy: epoch_hit as f64 / (epoch_hit + epoch_miss) as f64,
+                val_loss,
+                val_accuracy,
+            };
+            callbacks
+                .iter_mut()
+                .for_each(|callback| callback.on_epoch_end(self, epoch, &logs));
+            history.epochs.push(EpochRecord {
+                epoch,
+                loss: logs.loss,
+                accuracy: logs.accuracy,
+                val_loss,
+                val_accuracy,
+                learning_rate,
+                seconds: epoch_start.elapsed().as_secs_f64(),
+            });
+            if let Some(reason) = self.stop_training.take() {
+                history.stop_reason = StopReason::Stopped(reason);
+                break;
+            }
         }
+        callbacks
+            .iter_mut()
+            .for_each(|callback| callback.on_train_end(self));
+        // keep callbacks added while fitting
+        callbacks.append(&mut self.callbacks);
+        self.callbacks = callbacks;
+
+        history
+    }
+
+    // mean loss, with the regularizers' penalty, and accuracy over inputs in
+    // minibatches of batch_size without training, eg. on validation or test data
+    // return: (loss, accuracy)
+    pub fn evaluate(
+        &mut self,
+        inputs: &[Vec<f64>],
+        expecteds: &[Vec<f64>],
+        batch_size: usize,
+    ) -> (f64, f64) {
+        assert_eq!(inputs.len(), expecteds.len());
+        assert!(!inputs.is_empty(), "nothing to evaluate");
+        let penalty = self.penalty();
+        let (hit_count, loss) = inputs
+            .chunks(batch_size)
+            .zip(expecteds.chunks(batch_size))
+            .fold((0, 0.), |(hit_count, loss), (inputs, expecteds)| {
+                let logits = self.logits(inputs);
+                (
+                    hit_count
+                        + self
+                            .objective
+                            .hits(&logits, expecteds)
+                            .into_iter()
+                            .filter(|&hit| hit)
+                            .count(),
+                    loss + self.objective.loss(&logits, expecteds).iter().sum::<f64>(),
+                )
+            });
+        let num_samples = inputs.len() as f64;
+        (loss / num_samples + penalty, hit_count as f64 / num_samples)
+    }
 
-        println!("Loss:");
-        let losses: Vec<(f32, f32)> = all_batch_mean_loss
+    // sum of the regularizers' penalties of all layers' weights
+    fn penalty(&self) -> f64 {
+        self.layers
             .iter()
-            .enumerate()
-            .map(|(i, &v)| (i as f32, v as f32))
+            .filter_map(|layer| {
+                layer
+                    .regularizer
+                    .as_ref()
+                    .map(|regularizer| regularizer.penalty(&layer.weights))
+            })
+            .sum()
+    }
+
+    // validation inputs and expecteds, either the validation data or held
+    // out from the end of samples, held out samples are not weighted or masked
+    #[allow(clippy::type_complexity)]
+    fn take_validation(&self, samples: &mut Vec<Sample>) -> Option<(Vec<Vec<f64>>, Vec<Vec<f64>>)> {
+        match &self.validation {
+            Some(Validation::Data(inputs, expecteds)) => {
+                assert_eq!(inputs.len(), expecteds.len());
+                assert!(!inputs.is_empty(), "validation data is empty");
+                Some((inputs.clone(), expecteds.clone()))
+            }
+            Some(split) => {
+                let num_samples = samples.len();
+                let num_held_out = split.num_held_out(num_samples);
+                assert!(num_held_out < num_samples, "no samples left to train");
+                Some(
+                    samples
+                        .split_off(num_samples - num_held_out)
+                        .into_iter()
+                        .map(|sample| (sample.input, sample.expected))
+                        .unzip(),
+                )
+            }
+            None => None,
+        }
+    }
+
+    // fit full batch with an optimizer over all layers' parameters at once,
+    // eg. LBFGS for small models, instead of the network's optimizer
+    // every iteration is an epoch of one minibatch in the history and for the
+    // callbacks, with the loss and accuracy before its step
+    // constraints are applied after every step, the network's optimizer,
+    // learning rate scheduler, gradient clipping and weight averaging are not used
+    pub fn fit_with<C: ClosureOptimizer>(
+        &mut self,
+        optimizer: &mut C,
+        inputs: Vec<Vec<f64>>,
+        expecteds: Vec<Vec<f64>>,
+        iterations: usize,
+    ) -> TrainingHistory {
+        debug_assert_eq!(inputs[0].len(), self.layers[0].weights[0].len());
+        debug_assert!(
+            self.objective
+                .is_valid_label(&expecteds[0], self.layers.last().unwrap().weights.len()),
+            "label doesn't fit the output layer"
+        );
+        assert_eq!(inputs.len(), expecteds.len());
+        let mut history = TrainingHistory::new();
+        let mut samples: Vec<Sample> = inputs
+            .into_iter()
+            .zip(expecteds)
+            .map(|(input, expected)| Sample {
+                input,
+                expected,
+                weight: 1.,
+                mask: None,
+            })
             .collect();
-        let xmax = all_batch_mean_loss.len() as f32;
-        Chart::new(180, 100, 0., xmax)
-            .lineplot(&Shape::Lines(&losses))
-            .nice();
+        let validation = self.take_validation(&mut samples);
+        let (inputs, expecteds): (Vec<Vec<f64>>, Vec<Vec<f64>>) = samples
+            .iter()
+            .map(|sample| (sample.input.clone(), sample.expected.clone()))
+            .unzip();
+        let num_samples = samples.len();
+        let params = FitParams {
+            epochs: iterations,
+            batch_size: num_samples,
+            num_samples,
+            num_batches: 1,
+        };
+        // callbacks get the network mutably, so they are taken out while fitting
+        let mut callbacks = mem::take(&mut self.callbacks);
+        self.stop_training = None;
+        callbacks
+            .iter_mut()
+            .for_each(|callback| callback.on_train_begin(self, &params));
+
+        let mut parameters = self.parameters();
+        for i in 0..iterations {
+            let epoch = self.epochs;
+            let epoch_start = Instant::now();
+            callbacks
+                .iter_mut()
+                .for_each(|callback| callback.on_epoch_begin(self, epoch));
+            callbacks
+                .iter_mut()
+                .for_each(|callback| callback.on_batch_begin(self, epoch, 0));
+            let (_, accuracy) = self.evaluate(&inputs, &expecteds, num_samples);
+            let loss = optimizer.step(&mut parameters, |parameters| {
+                self.set_parameters(parameters);
+                let (_, _, loss, weight) = self.fit_one_batch(&samples);
+                let mean_gradients = self.take_mean_gradients();
+                self.gradient_norm = global_norm(&mean_gradients);
+                let gradients = mean_gradients
+                    .into_iter()
+                    .flat_map(|(gradients, bias_gradients)| {
+                        gradients.into_iter().flatten().chain(bias_gradients)
+                    })
+                    .collect();
+                (weighted_mean(loss, weight), gradients)
+            });
+            // project the accepted parameters, the next step starts from them
+            self.set_parameters(&parameters);
+            self.layers.iter_mut().for_each(|layer| {
+                if let Some(constraint) = &layer.constraint {
+                    constraint.apply(&mut layer.weights);
+                }
+            });
+            parameters = self.parameters();
+            self.steps += 1;
+            self.epochs += 1;
+            log::info!("iteration:[{}, acc:{:.3}, loss:{:.3}]", i, accuracy, loss);
+
+            let learning_rate = optimizer.learning_rate();
+            let seconds = epoch_start.elapsed().as_secs_f64();
+            history.batches.push(BatchRecord {
+                epoch,
+                batch: 0,
+                loss,
+                accuracy,
+                learning_rate,
+                gradient_norm: self.gradient_norm,
+                seconds,
+            });
+            let logs = BatchLogs {
+                loss,
+                accuracy,
+                num_samples,
+                gradient_norm: self.gradient_norm,
+            };
+            callbacks
+                .iter_mut()
+                .for_each(|callback| callback.on_batch_end(self, epoch, 0, &logs));
+            let (val_loss, val_accuracy) = match &validation {
+                Some((inputs, expecteds)) => {
+                    let (loss, accuracy) = self.evaluate(inputs, expecteds, inputs.len());
+                    (Some(loss), Some(accuracy))
+                }
+                None => (None, None),
+            };
+            let logs = EpochLogs {
+                loss,
+                accuracy,
+                val_loss,
+                val_accuracy,
+            };
+            callbacks
+                .iter_mut()
+                .for_each(|callback| callback.on_epoch_end(self, epoch, &logs));
+            history.epochs.push(EpochRecord {
+                epoch,
+                loss,
+                accuracy,
+                val_loss,
+                val_accuracy,
+                learning_rate,
+                seconds: epoch_start.elapsed().as_secs_f64(),
+            });
+            if let Some(reason) = self.stop_training.take() {
+                history.stop_reason = StopReason::Stopped(reason);
+                break;
+            }
+        }
+        callbacks
+            .iter_mut()
+            .for_each(|callback| callback.on_train_end(self));
+        // keep callbacks added while fitting
+        callbacks.append(&mut self.callbacks);
+        self.callbacks = callbacks;
+
+        history
+    }
+
+    // all layers' weights and bias flattened, in layer order with each
+    // layer's weights row by row then its bias
+    pub fn parameters(&self) -> Vec<f64> {
+        self.collect_parameters(true)
+    }
+
+    // parameters: all layers' weights and bias flattened, same as parameters()
+    pub fn set_parameters(&mut self, parameters: &[f64]) {
+        self.load_parameters(parameters, true);
+    }
 
-        all_batch_mean_loss
+    // parameters() of the layers which aren't frozen
+    pub(crate) fn trainable_parameters(&self) -> Vec<f64> {
+        self.collect_parameters(false)
     }
 
-    // (batch_hit, batch_miss, batch_loss)
-    fn fit_one_batch(&mut self, train_pairs: &[(Vec<f64>, Vec<f64>)]) -> (usize, usize, f64) {
-        let num_of_minibatch = train_pairs.len() as f64;
+    // parameters: same as trainable_parameters(), frozen layers are kept
+    pub(crate) fn set_trainable_parameters(&mut self, parameters: &[f64]) {
+        self.load_parameters(parameters, false);
+    }
+
+    fn collect_parameters(&self, with_frozen: bool) -> Vec<f64> {
+        self.layers
+            .iter()
+            .filter(|layer| with_frozen || !layer.frozen)
+            .flat_map(|layer| {
+                layer
+                    .weights
+                    .iter()
+                    .flatten()
+                    .chain(layer.bias.iter())
+                    .cloned()
+            })
+            .collect()
+    }
 
-        // step1. split inputs and ecpecteds from train_pairs and collect separately
+    fn load_parameters(&mut self, parameters: &[f64], with_frozen: bool) {
+        let mut parameters = parameters.iter();
+        self.layers
+            .iter_mut()
+            .filter(|layer| with_frozen || !layer.frozen)
+            .for_each(|layer| {
+                layer
+                    .weights
+                    .iter_mut()
+                    .flatten()
+                    .chain(layer.bias.iter_mut())
+                    .for_each(|param| *param = *parameters.next().expect("too few parameters"))
+            });
+        assert!(parameters.next().is_none(), "too many parameters");
+    }
+
+    // forward and backward one minibatch, and accumulate its gradients for
+    // the next optimizer step
+    // return: (batch_hit, batch_miss, batch_loss, batch_weight)
+    // batch_loss is the weighted sum, batch_loss / batch_weight is the mean
+    fn fit_one_batch(&mut self, samples: &[Sample]) -> (usize, usize, f64, f64) {
+        // step1. split inputs, expecteds, weights and masks from samples and collect separately
         let mut inputs = vec![];
         let mut expecteds = vec![];
-        train_pairs.into_iter().for_each(|(input, expected)| {
-            inputs.push(input.clone());
-            expecteds.push(expected.clone());
+        let mut weights = vec![];
+        samples.iter().for_each(|sample| {
+            inputs.push(sample.input.clone());
+            expecteds.push(sample.expected.clone());
+            weights.push(sample.weight);
         });
+        // samples without mask have all outputs labelled
+        let masks: Option<Vec<Vec<f64>>> = if samples.iter().any(|s| s.mask.is_some()) {
+            Some(
+                samples
+                    .iter()
+                    .map(|sample| {
+                        sample
+                            .mask
+                            .clone()
+                            .unwrap_or_else(|| vec![1.; self.layers.last().unwrap().weights.len()])
+                    })
+                    .collect(),
+            )
+        } else {
+            None
+        };
+        let sum_of_weights: f64 = weights.iter().sum();
 
         // step2. feed-forward
         // calculate the outputs of each layer in order
         let outputs = self.forward(&inputs);
 
         // step3. back propagation
-        let all_layer_minibatch_gradients = self.backward(&outputs, &expecteds);
+        // the objective sees the whole minibatch, so a sample's delta may depend
+        // on the other samples, eg. pairs and triplets for metric learning
+        let all_layer_minibatch_gradients =
+            self.backward(&outputs, &expecteds, &weights, masks.as_deref());
 
-        // step4. optimize
-        // use mean of minibatch's gradients currently
+        // step4. accumulate gradients
+        // sum minibatch's gradients into the gradients accumulated since the
+        // last optimizer step, the weights are already applied to the deltas
         // Vec1<(Vec2<Vec3<Vec4<f64>>>, Vec2<Vec3<f64>>)>
         // =>
-        // Vec1<(Mean<Vec3<Vec4<f64>>>, Mean<Vec3<f64>>)>
-        let mut mean_gradients: Vec<(Vec<Vec<f64>>, Vec<f64>)> = all_layer_minibatch_gradients
+        // Vec1<(Sum<Vec3<Vec4<f64>>>, Sum<Vec3<f64>>)>
+        // layers without gradients, eg. frozen ones, get zero gradients
+        if self.accumulated_gradients.is_empty() {
+            self.accumulated_gradients = self
+                .layers
+                .iter()
+                .map(|layer| {
+                    let num_nodes = layer.weights.len();
+                    let input_dim = layer.weights[0].len();
+                    (vec![vec![0.; input_dim]; num_nodes], vec![0.; num_nodes])
+                })
+                .collect();
+        }
+        all_layer_minibatch_gradients
             .iter()
-            .map(|(batch_gradients, batch_bias_gradients)| {
-                let num_nodes = batch_gradients[0].len();
-                let input_dim = batch_gradients[0][0].len();
-                let mut mean_gradients: Vec<Vec<f64>> = vec![vec![0.; input_dim]; num_nodes];
-                batch_gradients.iter().for_each(|gradients| {
-                    transform_matrix(&mut mean_gradients, gradients, |sum_g, g| {
-                        *sum_g = *sum_g + g
-                    })
-                });
-                mean_gradients.iter_mut().for_each(|row| {
-                    row.iter_mut()
-                        .for_each(|col| *col = *col / num_of_minibatch)
-                });
+            .zip(self.accumulated_gradients.iter_mut())
+            .for_each(
+                |((batch_gradients, batch_bias_gradients), (sum_gradients, sum_bias_gradients))| {
+                    batch_gradients.iter().for_each(|gradients| {
+                        transform_matrix(sum_gradients, gradients, |sum_g, g| *sum_g += g)
+                    });
+                    batch_bias_gradients.iter().for_each(|bias_gradients| {
+                        transform_vec(sum_bias_gradients, bias_gradients, |sum_bias_g, bias_g| {
+                            *sum_bias_g += bias_g
+                        })
+                    });
+                },
+            );
+        self.accumulated_weight += sum_of_weights;
 
-                let mut mean_bias_gradients: Vec<f64> = vec![0.; num_nodes];
-                batch_bias_gradients.iter().for_each(|bias_gradients| {
-                    transform_vec(
-                        &mut mean_bias_gradients,
-                        bias_gradients,
-                        |sum_bias_g, bias_g| *sum_bias_g = *sum_bias_g + bias_g,
-                    )
-                });
-                mean_bias_gradients
-                    .iter_mut()
-                    .for_each(|row| *row = *row / num_of_minibatch);
+        // step5. evaluation
+        // hit_count, miss_count, loss
+        // penalty of the weights the outputs were calculated with
+        let penalty = self.penalty();
+        // the penalty is part of every sample's loss, so the mean loss includes it once
+        let loss = penalty * sum_of_weights
+            + self
+                .objective
+                .weighted_loss(
+                    outputs.last().unwrap(),
+                    &expecteds,
+                    &weights,
+                    masks.as_deref(),
+                )
+                .iter()
+                .sum::<f64>();
+        let hit_count = self
+            .objective
+            .hits(outputs.last().unwrap(), &expecteds)
+            .into_iter()
+            .filter(|&hit| hit)
+            .count();
+        let miss_count = expecteds.len() - hit_count;
+        (hit_count, miss_count, loss, sum_of_weights)
+    }
 
-                (mean_gradients, mean_bias_gradients)
-            })
-            .collect();
+    // optimize with the gradients accumulated since the last step
+    fn step(&mut self) {
+        // step1. mean gradients with regularizers' gradients
+        let mut mean_gradients = self.take_mean_gradients();
+
+        // step2. clip mean gradients, and keep the norm before clipping for monitoring
+        self.gradient_norm = match &self.gradient_clip {
+            Some(gradient_clip) => gradient_clip.clip(&mut mean_gradients),
+            None => global_norm(&mean_gradients),
+        };
 
+        // step3. optimize
+        // layers without gradients keep their optimizer state, and weight decay
+        // doesn't shrink them
+        let trained = self.trained_layers();
         let optimizer = &mut self.optimizer;
         self.layers
             .iter_mut()
             .zip(mean_gradients.iter_mut())
             .enumerate()
+            .filter(|(idx, _)| trained[*idx])
             .for_each(|(idx, (ref mut layer, (gradient, bias_gradient)))| {
+                let lr_multiplier = layer.lr_multiplier;
                 let weights = &mut layer.weights;
                 let bias = &mut layer.bias;
-                optimizer.optimize(idx, weights, bias, gradient, bias_gradient);
+                optimizer.optimize_with_lr_multiplier(
+                    lr_multiplier,
+                    idx,
+                    weights,
+                    bias,
+                    gradient,
+                    bias_gradient,
+                );
+                if let Some(constraint) = &layer.constraint {
+                    constraint.apply(weights);
+                }
             });
+        if let Some(averaged_weights) = self.averaged_weights.as_mut() {
+            averaged_weights.on_step(&self.layers);
+        }
+        self.steps += 1;
+    }
 
-        // step5. evaluation
-        // hit_count, miss_count, loss
-        let loss = self
-            .objective
-            .loss(&outputs.last().unwrap(), &expecteds)
+    // weighted mean of the gradients accumulated since the last step, with
+    // regularizers' gradients added, and start accumulating again
+    fn take_mean_gradients(&mut self) -> Vec<(Vec<Vec<f64>>, Vec<f64>)> {
+        // use weighted mean of accumulated gradients, so divide the sum by the
+        // sum of weights, all zero weights leave zero gradients
+        let num_of_minibatch = if self.accumulated_weight > 0. {
+            self.accumulated_weight
+        } else {
+            1.
+        };
+        let mut mean_gradients = mem::take(&mut self.accumulated_gradients);
+        self.accumulated_weight = 0.;
+        mean_gradients
+            .iter_mut()
+            .for_each(|(gradients, bias_gradients)| {
+                gradients
+                    .iter_mut()
+                    .flatten()
+                    .chain(bias_gradients.iter_mut())
+                    .for_each(|g| *g /= num_of_minibatch)
+            });
+
+        let trained = self.trained_layers();
+        self.layers
             .iter()
-            .sum();
-        let outputs = self.objective.predict_from_logits(&outputs.last().unwrap());
-        let (hit_count, miss_count) = outputs.iter().zip(expecteds.iter()).fold(
-            (0, 0),
-            |(hit_count, miss_count), (output, expected)| {
-                let hit = output
-                    .iter()
-                    .zip(expected)
-                    .fold(true, |eq, (l, r)| eq && if l == r { true } else { false });
-                if hit {
-                    (hit_count + 1, miss_count)
-                } else {
-                    (hit_count, miss_count + 1)
+            .zip(mean_gradients.iter_mut())
+            .zip(trained)
+            .filter(|(_, trained)| *trained)
+            .for_each(|((layer, (gradients, _)), _)| {
+                if let Some(regularizer) = &layer.regularizer {
+                    transform_matrix(gradients, &regularizer.gradient(&layer.weights), |g, r| {
+                        *g += r
+                    });
                 }
-            },
-        );
-        (hit_count, miss_count, loss)
+            });
+        mean_gradients
+    }
+
+    // whether each layer gets gradients from backward, ie. it isn't frozen and
+    // all the following layers propagate deltas
+    fn trained_layers(&self) -> Vec<bool> {
+        (0..self.layers.len())
+            .map(|idx| {
+                !self.layers[idx].frozen
+                    && self.layers[idx + 1..]
+                        .iter()
+                        .all(|layer| layer.propagate_deltas)
+            })
+            .collect()
     }
 
     // infer with pre-trained weights
     pub fn infer(&mut self, input: &[f64]) -> Vec<f64> {
         let outputs = self.forward(&[Vec::from(input)]);
         self.objective
-            .predict_from_logits(&outputs.last().unwrap())
+            .predict_from_logits(outputs.last().unwrap())
             .first()
             .unwrap()
             .clone()
     }
 
+    // infer with averaged weights, or the current weights before anything is averaged
+    pub fn infer_averaged(&mut self, input: &[f64]) -> Vec<f64> {
+        self.swap_averaged_weights();
+        let output = self.infer(input);
+        self.swap_averaged_weights();
+        output
+    }
+
+    // evaluate with averaged weights, or the current weights before anything is averaged
+    pub fn evaluate_averaged(
+        &mut self,
+        inputs: &[Vec<f64>],
+        expecteds: &[Vec<f64>],
+        batch_size: usize,
+    ) -> (f64, f64) {
+        self.swap_averaged_weights();
+        let metrics = self.evaluate(inputs, expecteds, batch_size);
+        self.swap_averaged_weights();
+        metrics
+    }
+
+    // replace the weights with averaged weights, eg. at the end of training
+    // averaging restarts from the replaced weights
+    pub fn apply_averaged_weights(&mut self) {
+        self.swap_averaged_weights();
+        if let Some(averaged_weights) = self.averaged_weights.as_mut() {
+            averaged_weights.reset();
+        }
+    }
+
+    // swap layers' weights and bias with averaged ones, if any
+    fn swap_averaged_weights(&mut self) {
+        if let Some(averaged_weights) = self.averaged_weights.as_mut() {
+            if averaged_weights.is_empty() {
+                return;
+            }
+            self.layers
+                .iter_mut()
+                .zip(averaged_weights.weights.iter_mut())
+                .for_each(|(layer, (weights, bias))| {
+                    mem::swap(&mut layer.weights, weights);
+                    mem::swap(&mut layer.bias, bias);
+                });
+        }
+    }
+
+    // raw outputs of the last layer before the objective's activation,
+    // eg. a teacher's logits for knowledge distillation
+    // inputs: minibatch of Vec<f64>
+    // return: minibatch of logits
+    pub fn logits(&mut self, inputs: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        self.forward(inputs).pop().unwrap()
+    }
+
     // calc the outputs of each layer in order
     // put the input first in the outputs
     // inputs: minibatch of Vec<f64>
@@ -207,16 +925,25 @@ impl<A: Activator, Obj: Objective<A>, Opt: Optimizer> Network<A, Obj, Opt> {
 
     // outputs: all layers' of minibatch outputs include inputs
     // expected: minibatch of labels
-    // return: all layers' of (minibatch gradients, minibatch bias_gradients)
+    // weights: minibatch of sample weights
+    // masks: minibatch of output masks
+    // return: all layers' of (minibatch gradients, minibatch bias_gradients),
+    //         empty for layers without gradients, eg. frozen ones
+    #[allow(clippy::type_complexity)]
     fn backward(
         &mut self,
         outputs: &[Vec<Vec<f64>>],
         expecteds: &[Vec<f64>],
+        weights: &[f64],
+        masks: Option<&[Vec<f64>]>,
     ) -> Vec<(Vec<Vec<Vec<f64>>>, Vec<Vec<f64>>)> {
         let mut all_layer_gradients: Vec<(Vec<Vec<Vec<f64>>>, Vec<Vec<f64>>)> = vec![];
-        let mut delta_without_derivs = self
-            .objective
-            .delta_without_deriv(&outputs.last().unwrap(), expecteds);
+        let mut delta_without_derivs = self.objective.weighted_delta_without_deriv(
+            outputs.last().unwrap(),
+            expecteds,
+            weights,
+            masks,
+        );
 
         // loop through the layers backwards and propagate the error throughout
         let mut layers_backwards: Vec<&Layer> = self.layers.iter().map(AsRef::as_ref).collect();
@@ -229,16 +956,484 @@ impl<A: Activator, Obj: Objective<A>, Opt: Optimizer> Network<A, Obj, Opt> {
         for (k, layer) in layers_backwards.iter().enumerate() {
             let layer_k = num_layers - k;
 
-            let (deltas, gradients, bias_gradients) = layer.delta_without_deriv_and_gradient(
-                &delta_without_derivs,
-                &outputs[layer_k],
-                &outputs[layer_k - 1],
-            );
-            all_layer_gradients.push((gradients, bias_gradients));
+            // previous layers need deltas only if some of them are trained
+            let propagate = layer.propagate_deltas
+                && layers_backwards[(k + 1)..]
+                    .iter()
+                    .any(|layer| !layer.frozen);
+
+            if layer.frozen {
+                all_layer_gradients.push((vec![], vec![]));
+                if propagate {
+                    delta_without_derivs =
+                        layer.delta_without_deriv(&delta_without_derivs, &outputs[layer_k]);
+                }
+            } else {
+                let (deltas, gradients, bias_gradients) = layer.delta_without_deriv_and_gradient(
+                    &delta_without_derivs,
+                    &outputs[layer_k],
+                    &outputs[layer_k - 1],
+                );
+                all_layer_gradients.push((gradients, bias_gradients));
+                delta_without_derivs = deltas;
+            }
 
-            delta_without_derivs = deltas;
+            if !propagate {
+                break;
+            }
         }
+        // the previous layers got no deltas
+        all_layer_gradients.resize_with(num_layers, || (vec![], vec![]));
         all_layer_gradients.reverse();
         all_layer_gradients
     }
 }
+
+// sum / weight, or 0 for all zero weights instead of NaN
+fn weighted_mean(sum: f64, weight: f64) -> f64 {
+    if weight == 0. {
+        0.
+    } else {
+        sum / weight
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Network;
+    use crate::activators::Sigmoid;
+    use crate::callbacks::{BatchLogs, Callback, EarlyStopping, EpochLogs, FitParams};
+    use crate::constraints::NonNeg;
+    use crate::gradient_clip::GradientClip;
+    use crate::history::StopReason;
+    use crate::initializers::{Constant, Orthogonal, Zeros};
+    use crate::objectives::BinaryCrossEntropy;
+    use crate::optimizers::{Adam, LBFGS, SGD};
+    use crate::regularizers::L2;
+    use crate::validation::Validation;
+    use crate::weight_averaging::WeightAveraging;
+    use crate::{LayerOptions, NetworkBuilder};
+
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_frozen_layers() {
+        let mut nn = NetworkBuilder::new()
+            .input(2)
+            .add_layer(3, Box::new(Sigmoid))
+            .frozen()
+            .add_layer(3, Box::new(Sigmoid))
+            .with_lr_multiplier(0.1)
+            .output(1)
+            .minimize_to(BinaryCrossEntropy::new())
+            .optimize_with(Adam::new(0.01))
+            .build();
+        let inputs = vec![vec![0., 0.], vec![0., 1.], vec![1., 0.], vec![1., 1.]];
+        let labels = vec![vec![0.], vec![1.], vec![1.], vec![0.]];
+
+        let weights = nn.layers[0].weights.clone();
+        let hidden_weights = nn.layers[1].weights.clone();
+        nn.fit(inputs.clone(), labels.clone(), 2, 2);
+        assert_eq!(nn.layers[0].weights, weights);
+        assert_ne!(nn.layers[1].weights, hidden_weights);
+
+        // unfreeze mid-training
+        nn.set_frozen(0, false);
+        nn.fit(inputs, labels, 2, 2);
+        assert_ne!(nn.layers[0].weights, weights);
+    }
+
+    #[test]
+    fn test_propagate_deltas() {
+        let mut nn = NetworkBuilder::new()
+            .input(2)
+            .add_layer(3, Box::new(Sigmoid))
+            .output(1)
+            .minimize_to(BinaryCrossEntropy::new())
+            .optimize_with(SGD::new(0.1).with_momentum(0.9).with_weight_decay(0.1))
+            .build();
+        nn.set_propagate_deltas(1, false);
+        let inputs = vec![vec![0., 0.], vec![0., 1.], vec![1., 0.], vec![1., 1.]];
+        let labels = vec![vec![0.], vec![1.], vec![1.], vec![0.]];
+
+        // the hidden layer gets no gradients, so neither momentum nor weight decay moves it
+        let weights = nn.layers[0].weights.clone();
+        let bias = nn.layers[0].bias.clone();
+        let output_weights = nn.layers[1].weights.clone();
+        nn.fit(inputs, labels, 2, 2);
+        assert_eq!(nn.layers[0].weights, weights);
+        assert_eq!(nn.layers[0].bias, bias);
+        assert_ne!(nn.layers[1].weights, output_weights);
+    }
+
+    #[test]
+    fn test_gradient_accumulation() {
+        let build = |accumulation_steps| {
+            NetworkBuilder::new()
+                .input(2)
+                .add_layer_with_weights_and_bias(
+                    2,
+                    Box::new(Sigmoid),
+                    vec![vec![0.5, -0.4], vec![0.3, 0.8]],
+                    vec![0.1, -0.1],
+                )
+                .output_with_weights_and_bias(1, vec![vec![0.7, -0.6]], vec![0.2])
+                .minimize_to(BinaryCrossEntropy::new())
+                .optimize_with(SGD::new(0.5))
+                .accumulate_gradients(accumulation_steps)
+                .build()
+        };
+        let inputs = vec![vec![0., 0.], vec![0., 1.], vec![1., 0.], vec![1., 1.]];
+        let labels = vec![vec![0.], vec![1.], vec![1.], vec![0.]];
+
+        // one minibatch of 4 and two accumulated minibatches of 2
+        let mut nn = build(1);
+        nn.fit(inputs.clone(), labels.clone(), 1, 4);
+        let mut accumulated = build(2);
+        accumulated.fit(inputs, labels, 1, 2);
+        for (layer, accumulated_layer) in nn.layers.iter().zip(accumulated.layers.iter()) {
+            for (w, accumulated_w) in layer.weights.iter().flatten().chain(layer.bias.iter()).zip(
+                accumulated_layer
+                    .weights
+                    .iter()
+                    .flatten()
+                    .chain(accumulated_layer.bias.iter()),
+            ) {
+                assert!((w - accumulated_w).abs() < 1e-12);
+            }
+        }
+        assert_eq!(accumulated.steps, 1);
+    }
+
+    #[test]
+    fn test_gradient_clipping() {
+        let mut nn = NetworkBuilder::new()
+            .input(2)
+            .output_with_weights_and_bias(1, vec![vec![0.5, -2.]], vec![0.1])
+            .minimize_to(BinaryCrossEntropy::new())
+            .optimize_with(SGD::new(1.))
+            .clip_gradients(GradientClip::GlobalNorm(1e-3))
+            .build();
+        let inputs = vec![vec![1., 1.], vec![1., 0.]];
+        let labels = vec![vec![0.], vec![1.]];
+        let history = nn.fit(inputs, labels, 1, 2);
+
+        // the step is clipped to the max norm times the learning rate
+        let step = nn.layers[0].weights[0]
+            .iter()
+            .zip([0.5, -2.].iter())
+            .chain(nn.layers[0].bias.iter().zip([0.1].iter()))
+            .map(|(w, initial)| (w - initial).powi(2))
+            .sum::<f64>()
+            .sqrt();
+        assert!((step - 1e-3).abs() < 1e-12);
+        // the logged norm is the one before clipping
+        assert!(nn.gradient_norm() > 1e-3);
+        assert_eq!(history.batches[0].gradient_norm, nn.gradient_norm());
+    }
+
+    #[test]
+    fn test_fit_with_lbfgs() {
+        let mut nn = NetworkBuilder::new()
+            .with_seed(3)
+            .input(2)
+            .add_layer(4, Box::new(Sigmoid))
+            .output(1)
+            .minimize_to(BinaryCrossEntropy::new())
+            .optimize_with(SGD::new(0.1))
+            .build();
+        let inputs = vec![vec![0., 0.], vec![0., 1.], vec![1., 0.], vec![1., 1.]];
+        let labels = vec![vec![0.], vec![1.], vec![1.], vec![0.]];
+
+        let parameters = nn.parameters();
+        nn.set_parameters(&parameters);
+        assert_eq!(nn.parameters(), parameters);
+
+        let history = nn.fit_with(&mut LBFGS::new(1.), inputs, labels, 20);
+        let losses = history.batch_losses();
+        assert_eq!(losses.len(), 20);
+        assert!(losses.last().unwrap() < &losses[0]);
+    }
+
+    // records hooks, and halves the learning rate every epoch
+    struct Recorder {
+        hooks: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl Callback<Sigmoid, BinaryCrossEntropy, SGD> for Recorder {
+        fn on_train_begin(
+            &mut self,
+            _: &mut Network<Sigmoid, BinaryCrossEntropy, SGD>,
+            params: &FitParams,
+        ) {
+            assert_eq!(params.num_batches, 2);
+            self.hooks.borrow_mut().push("train_begin".to_string());
+        }
+
+        fn on_batch_end(
+            &mut self,
+            _: &mut Network<Sigmoid, BinaryCrossEntropy, SGD>,
+            epoch: usize,
+            batch: usize,
+            logs: &BatchLogs,
+        ) {
+            assert_eq!(logs.num_samples, 2);
+            self.hooks
+                .borrow_mut()
+                .push(format!("batch_end {} {}", epoch, batch));
+        }
+
+        fn on_epoch_end(
+            &mut self,
+            network: &mut Network<Sigmoid, BinaryCrossEntropy, SGD>,
+            epoch: usize,
+            logs: &EpochLogs,
+        ) {
+            assert!(logs.loss > 0.);
+            let optimizer = network.optimizer_mut();
+            optimizer.learning_rate /= 2.;
+            self.hooks.borrow_mut().push(format!("epoch_end {}", epoch));
+        }
+    }
+
+    #[test]
+    fn test_callbacks() {
+        let hooks = Rc::new(RefCell::new(vec![]));
+        let mut nn = NetworkBuilder::new()
+            .input(2)
+            .add_layer(2, Box::new(Sigmoid))
+            .output(1)
+            .minimize_to(BinaryCrossEntropy::new())
+            .optimize_with(SGD::new(0.8))
+            .with_callback(Recorder {
+                hooks: Rc::clone(&hooks),
+            })
+            .build();
+        let inputs = vec![vec![0., 0.], vec![0., 1.], vec![1., 0.], vec![1., 1.]];
+        let labels = vec![vec![0.], vec![1.], vec![1.], vec![0.]];
+        nn.fit(inputs, labels, 2, 2);
+        assert_eq!(nn.optimizer().learning_rate, 0.2);
+        assert_eq!(
+            *hooks.borrow(),
+            [
+                "train_begin",
+                "batch_end 0 0",
+                "batch_end 0 1",
+                "epoch_end 0",
+                "batch_end 1 0",
+                "batch_end 1 1",
+                "epoch_end 1"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_early_stopping() {
+        let mut nn = NetworkBuilder::new()
+            .input(2)
+            .output(1)
+            .minimize_to(BinaryCrossEntropy::new())
+            .optimize_with(SGD::new(0.))
+            .with_callback(EarlyStopping::new(1).with_min_delta(1e-9))
+            .build();
+        let inputs = vec![vec![0., 0.], vec![0., 1.], vec![1., 0.], vec![1., 1.]];
+        let labels = vec![vec![0.], vec![1.], vec![1.], vec![0.]];
+        // without validation data the loss is monitored, it never improves
+        // after the first epoch, so fit stops after the second instead of 10
+        let history = nn.fit(inputs, labels, 10, 4);
+        assert_eq!(history.batches.len(), 2);
+        assert_eq!(history.epochs.len(), 2);
+        assert!(matches!(history.stop_reason, StopReason::Stopped(_)));
+    }
+
+    #[test]
+    fn test_validation() {
+        let mut nn = NetworkBuilder::new()
+            .input(2)
+            .add_layer(2, Box::new(Sigmoid))
+            .output(1)
+            .minimize_to(BinaryCrossEntropy::new())
+            .optimize_with(SGD::new(0.5))
+            .validate_with(Validation::Split(0.25))
+            .build();
+        let inputs = vec![vec![0., 0.], vec![0., 1.], vec![1., 0.], vec![1., 1.]];
+        let labels = vec![vec![0.], vec![1.], vec![1.], vec![0.]];
+        let history = nn.fit(
+            [&inputs[..], &inputs].concat(),
+            [&labels[..], &labels].concat(),
+            2,
+            2,
+        );
+        // the last 2 of 8 samples are held out, leaving 3 minibatches per epoch
+        assert_eq!(history.batches.len(), 6);
+        let (val_loss, val_accuracy) = nn.evaluate(&inputs[2..], &labels[2..], 2);
+        let last = history.epochs.last().unwrap();
+        assert_eq!(last.val_loss, Some(val_loss));
+        assert_eq!(last.val_accuracy, Some(val_accuracy));
+    }
+
+    #[test]
+    fn test_validation_with_averaged_weights() {
+        let mut nn = NetworkBuilder::new()
+            .input(2)
+            .add_layer(2, Box::new(Sigmoid))
+            .output(1)
+            .minimize_to(BinaryCrossEntropy::new())
+            .optimize_with(SGD::new(0.5))
+            .average_weights(WeightAveraging::Ema(0.9))
+            .validate_with(Validation::Split(0.5))
+            .build();
+        let inputs = vec![vec![0., 0.], vec![0., 1.], vec![1., 0.], vec![1., 1.]];
+        let labels = vec![vec![0.], vec![1.], vec![1.], vec![0.]];
+        let history = nn.fit(inputs.clone(), labels.clone(), 2, 1);
+        let (val_loss, _) = nn.evaluate_averaged(&inputs[2..], &labels[2..], 1);
+        assert_eq!(history.epochs.last().unwrap().val_loss, Some(val_loss));
+        let (live_loss, _) = nn.evaluate(&inputs[2..], &labels[2..], 1);
+        assert_ne!(live_loss, val_loss);
+    }
+
+    #[test]
+    fn test_seed() {
+        let fit = |seed| {
+            let mut nn = NetworkBuilder::new()
+                .with_seed(seed)
+                .input(2)
+                .add_layer(3, Box::new(Sigmoid))
+                .output(1)
+                .minimize_to(BinaryCrossEntropy::new())
+                .optimize_with(Adam::new(0.1))
+                .build();
+            let initial = nn.parameters();
+            let inputs = vec![vec![0., 0.], vec![0., 1.], vec![1., 0.], vec![1., 1.]];
+            let labels = vec![vec![0.], vec![1.], vec![1.], vec![0.]];
+            let history = nn.fit(inputs, labels, 3, 1);
+            (initial, history.batch_losses(), nn.parameters())
+        };
+        assert_eq!(fit(7), fit(7));
+        assert_ne!(fit(7).0, fit(8).0);
+    }
+
+    #[test]
+    fn test_initializers() {
+        let nn = NetworkBuilder::new()
+            .input(2)
+            .add_layer_with_initializer(3, Box::new(Sigmoid), Box::new(Orthogonal::new()))
+            .with_bias_initializer(Box::new(Constant::new(0.1)))
+            .output_with_initializer(1, Box::new(Constant::new(0.5)))
+            .minimize_to(BinaryCrossEntropy::new())
+            .optimize_with(SGD::new(0.1))
+            .build();
+        let parameters = nn.parameters();
+        // 3x2 weights, 3 bias, 1x3 weights, 1 bias
+        assert_eq!(parameters.len(), 13);
+        assert_eq!(parameters[6..9], [0.1, 0.1, 0.1]);
+        assert_eq!(parameters[9..], [0.5, 0.5, 0.5, 0.]);
+
+        // with_initializer replaces given weights
+        let nn = NetworkBuilder::new()
+            .input(2)
+            .output_with_weights_and_bias(1, vec![vec![0.3, -0.2]], vec![0.1])
+            .with_initializer(Box::new(Zeros))
+            .minimize_to(BinaryCrossEntropy::new())
+            .optimize_with(SGD::new(0.1))
+            .build();
+        assert_eq!(nn.parameters(), [0., 0., 0.1]);
+    }
+
+    #[test]
+    fn test_zero_weights() {
+        let mut nn = NetworkBuilder::new()
+            .input(2)
+            .output(1)
+            .minimize_to(BinaryCrossEntropy::new())
+            .optimize_with(SGD::new(0.1))
+            .build();
+        let inputs = vec![vec![0., 0.], vec![0., 1.], vec![1., 0.], vec![1., 1.]];
+        let labels = vec![vec![0.], vec![1.], vec![1.], vec![0.]];
+        let history = nn.fit_weighted(inputs, labels, Some(vec![0.; 4]), None, 1, 2);
+        assert_eq!(history.batch_losses(), [0., 0.]);
+        assert_eq!(history.epochs[0].loss, 0.);
+    }
+
+    #[test]
+    fn test_fit_with_constraint() {
+        let mut nn = NetworkBuilder::new()
+            .input(2)
+            .output_with_weights_and_bias(1, vec![vec![0.1, 0.1]], vec![0.])
+            .with_constraint(Box::new(NonNeg))
+            .minimize_to(BinaryCrossEntropy::new())
+            .optimize_with(SGD::new(0.1))
+            .with_callback(EarlyStopping::new(1).with_min_delta(1e9))
+            .build();
+        // unconstrained, the second weight goes below 0
+        let inputs = vec![vec![1., 1.], vec![1., 0.]];
+        let labels = vec![vec![0.], vec![1.]];
+        let history = nn.fit_with(&mut LBFGS::new(1.), inputs, labels, 5);
+        assert!(nn.layers[0].weights[0].iter().all(|&w| w >= 0.));
+        // no improvement is large enough, so it stops after the second iteration
+        assert_eq!(history.epochs.len(), 2);
+        assert!(matches!(history.stop_reason, StopReason::Stopped(_)));
+    }
+
+    #[test]
+    fn test_penalty() {
+        let build = |lambda| {
+            NetworkBuilder::new()
+                .input(2)
+                .output_with_weights_and_bias(1, vec![vec![0.5, -2.]], vec![0.1])
+                .with_regularizer(Box::new(L2::new(lambda)))
+                .minimize_to(BinaryCrossEntropy::new())
+                .optimize_with(SGD::new(0.))
+                .build()
+        };
+        let inputs = vec![vec![0., 0.], vec![0., 1.], vec![1., 0.], vec![1., 1.]];
+        let labels = vec![vec![0.], vec![1.], vec![1.], vec![0.]];
+
+        // penalty = 0.1 * (0.5^2 + 2^2)
+        let mut nn = build(0.);
+        let mut regularized = build(0.1);
+        let history = nn.fit(inputs.clone(), labels.clone(), 1, 4);
+        let regularized_history = regularized.fit(inputs.clone(), labels.clone(), 1, 4);
+        assert!(
+            (regularized_history.batches[0].loss - history.batches[0].loss - 0.425).abs() < 1e-12
+        );
+        assert!(
+            (regularized_history.epochs[0].loss - history.epochs[0].loss - 0.425).abs() < 1e-12
+        );
+        let (loss, _) = nn.evaluate(&inputs, &labels, 4);
+        let (regularized_loss, _) = regularized.evaluate(&inputs, &labels, 4);
+        assert!((regularized_loss - loss - 0.425).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_constraint() {
+        let build = |constrained| {
+            let builder = NetworkBuilder::new().input(2).output_with_weights_and_bias(
+                1,
+                vec![vec![0.1, 0.1]],
+                vec![0.],
+            );
+            let builder = if constrained {
+                builder.with_constraint(Box::new(NonNeg))
+            } else {
+                builder
+            };
+            builder
+                .minimize_to(BinaryCrossEntropy::new())
+                .optimize_with(SGD::new(1.))
+                .build()
+        };
+        // the step pushes the second weight below 0
+        let inputs = vec![vec![1., 1.], vec![1., 0.]];
+        let labels = vec![vec![0.], vec![1.]];
+
+        let mut nn = build(false);
+        let mut constrained = build(true);
+        nn.fit(inputs.clone(), labels.clone(), 1, 2);
+        constrained.fit(inputs, labels, 1, 2);
+        assert!(nn.layers[0].weights[0].iter().any(|&w| w < 0.));
+        let clamped: Vec<f64> = nn.layers[0].weights[0].iter().map(|w| w.max(0.)).collect();
+        assert_eq!(constrained.layers[0].weights[0], clamped);
+    }
+}
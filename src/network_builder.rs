@@ -1,44 +1,152 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 use std::marker::PhantomData;
 
 use crate::activators::{Activator, Linear};
+use crate::callbacks::Callback;
+use crate::constraints::Constraint;
+use crate::gradient_clip::GradientClip;
+use crate::initializers::{He, Initializer};
 use crate::layers::Layer;
 use crate::network::Network;
 use crate::objectives::Objective;
 use crate::optimizers::Optimizer;
+use crate::regularizers::Regularizer;
+use crate::schedulers::LrScheduler;
+use crate::validation::Validation;
+use crate::weight_averaging::WeightAveraging;
 
-pub struct NetworkBuilder;
+// every stage carries the rng, which initializes the layers and then
+// shuffles the samples of fit
+pub struct NetworkBuilder {
+    rng: StdRng,
+}
+
+impl Default for NetworkBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl NetworkBuilder {
     pub fn new() -> NetworkBuilder {
-        NetworkBuilder
+        NetworkBuilder {
+            rng: StdRng::from_entropy(),
+        }
+    }
+
+    // the same seed gives the same initial weights, order of minibatches and
+    // so losses, for reproducible runs
+    pub fn with_seed(self, seed: u64) -> NetworkBuilder {
+        NetworkBuilder {
+            rng: StdRng::seed_from_u64(seed),
+        }
     }
 
     pub fn input(self, input_dim: usize) -> NetworkBuilderWithInput {
         NetworkBuilderWithInput {
             input_dim,
             layers: vec![],
+            rng: self.rng,
         }
     }
 }
 
+// options for the last added layer, shared by the builder stages that add
+// layers
+pub trait LayerOptions: Sized {
+    fn last_layer_mut(&mut self) -> &mut Layer;
+    fn rng_mut(&mut self) -> &mut StdRng;
+
+    // redraw the weights of the last added layer, replacing any weights given
+    // to add_layer_with_weights_and_bias, see add_layer_with_initializer to
+    // draw them only once
+    fn with_initializer(mut self, initializer: Box<dyn Initializer>) -> Self {
+        let (fan_out, fan_in) = {
+            let layer = self.last_layer_mut();
+            (layer.weights.len(), layer.weights[0].len())
+        };
+        let weights = initializer.init((fan_out, fan_in), fan_in, fan_out, self.rng_mut());
+        self.last_layer_mut().weights = weights;
+        self
+    }
+
+    // reinitialize the bias of the last added layer, zeros by default
+    fn with_bias_initializer(mut self, initializer: Box<dyn Initializer>) -> Self {
+        let (fan_out, fan_in) = {
+            let layer = self.last_layer_mut();
+            (layer.weights.len(), layer.weights[0].len())
+        };
+        let bias = initializer
+            .init((1, fan_out), fan_in, fan_out, self.rng_mut())
+            .remove(0);
+        self.last_layer_mut().bias = bias;
+        self
+    }
+
+    // penalize the weights of the last added layer
+    fn with_regularizer(mut self, regularizer: Box<dyn Regularizer>) -> Self {
+        self.last_layer_mut().regularizer = Some(regularizer);
+        self
+    }
+
+    // constrain the weights of the last added layer after every optimizer step
+    fn with_constraint(mut self, constraint: Box<dyn Constraint>) -> Self {
+        self.last_layer_mut().constraint = Some(constraint);
+        self
+    }
+
+    // keep the weights and bias of the last added layer, eg. pretrained ones
+    fn frozen(mut self) -> Self {
+        self.last_layer_mut().frozen = true;
+        self
+    }
+
+    // scale the optimizer's learning rate for the last added layer
+    fn with_lr_multiplier(mut self, lr_multiplier: f64) -> Self {
+        self.last_layer_mut().lr_multiplier = lr_multiplier;
+        self
+    }
+}
+
 pub struct NetworkBuilderWithInput {
     input_dim: usize,
+    #[allow(clippy::vec_box)]
     layers: Vec<Box<Layer>>,
+    rng: StdRng,
 }
 
 impl NetworkBuilderWithInput {
+    // weights drawn by He::new(), normal over fan_in, bias zeros
     pub fn add_layer(
+        self,
+        num_nodes: usize,
+        activator: Box<dyn Activator>,
+    ) -> NetworkBuilderWithInput {
+        self.add_layer_with_initializer(num_nodes, activator, Box::new(He::new()))
+    }
+
+    // weights drawn by initializer, bias zeros, see with_bias_initializer
+    pub fn add_layer_with_initializer(
         mut self,
         num_nodes: usize,
         activator: Box<dyn Activator>,
+        initializer: Box<dyn Initializer>,
     ) -> NetworkBuilderWithInput {
-        let layer = Layer::new(self.input_dim, num_nodes, activator, None, None);
+        let weights = initializer.init(
+            (num_nodes, self.input_dim),
+            self.input_dim,
+            num_nodes,
+            &mut self.rng,
+        );
+        let layer = Layer::new(self.input_dim, num_nodes, activator, Some(weights), None);
         self.layers.push(Box::new(layer));
 
         NetworkBuilderWithInput {
             // current num_nodes as next layer's input_dim
             input_dim: num_nodes,
             layers: self.layers,
+            rng: self.rng,
         }
     }
 
@@ -62,14 +170,38 @@ impl NetworkBuilderWithInput {
             // current num_nodes as next layer's input_dim
             input_dim: num_nodes,
             layers: self.layers,
+            rng: self.rng,
         }
     }
 
-    pub fn output(mut self, num_nodes: usize) -> NetworkBuilderWithOutput {
-        let layer = Layer::new(self.input_dim, num_nodes, Box::new(Linear), None, None);
+    // weights drawn by He::new(), normal over fan_in, bias zeros
+    pub fn output(self, num_nodes: usize) -> NetworkBuilderWithOutput {
+        self.output_with_initializer(num_nodes, Box::new(He::new()))
+    }
+
+    // weights drawn by initializer, bias zeros, see with_bias_initializer
+    pub fn output_with_initializer(
+        mut self,
+        num_nodes: usize,
+        initializer: Box<dyn Initializer>,
+    ) -> NetworkBuilderWithOutput {
+        let weights = initializer.init(
+            (num_nodes, self.input_dim),
+            self.input_dim,
+            num_nodes,
+            &mut self.rng,
+        );
+        let layer = Layer::new(
+            self.input_dim,
+            num_nodes,
+            Box::new(Linear),
+            Some(weights),
+            None,
+        );
         self.layers.push(Box::new(layer));
         NetworkBuilderWithOutput {
             layers: self.layers,
+            rng: self.rng,
         }
     }
 
@@ -89,12 +221,35 @@ impl NetworkBuilderWithInput {
         self.layers.push(Box::new(layer));
         NetworkBuilderWithOutput {
             layers: self.layers,
+            rng: self.rng,
         }
     }
 }
 
 pub struct NetworkBuilderWithOutput {
+    #[allow(clippy::vec_box)]
     layers: Vec<Box<Layer>>,
+    rng: StdRng,
+}
+
+impl LayerOptions for NetworkBuilderWithInput {
+    fn last_layer_mut(&mut self) -> &mut Layer {
+        self.layers.last_mut().expect("no layer added yet")
+    }
+
+    fn rng_mut(&mut self) -> &mut StdRng {
+        &mut self.rng
+    }
+}
+
+impl LayerOptions for NetworkBuilderWithOutput {
+    fn last_layer_mut(&mut self) -> &mut Layer {
+        self.layers.last_mut().unwrap()
+    }
+
+    fn rng_mut(&mut self) -> &mut StdRng {
+        &mut self.rng
+    }
 }
 
 impl NetworkBuilderWithOutput {
@@ -105,14 +260,17 @@ impl NetworkBuilderWithOutput {
         NetworkBuilderWithObjective {
             layers: self.layers,
             objective,
+            rng: self.rng,
             _marker: PhantomData,
         }
     }
 }
 
 pub struct NetworkBuilderWithObjective<A: Activator, Obj: Objective<A>> {
+    #[allow(clippy::vec_box)]
     layers: Vec<Box<Layer>>,
     objective: Obj,
+    rng: StdRng,
     _marker: PhantomData<A>,
 }
 
@@ -125,20 +283,99 @@ impl<A: Activator, Obj: Objective<A>> NetworkBuilderWithObjective<A, Obj> {
             layers: self.layers,
             objective: self.objective,
             optimizer,
+            rng: self.rng,
+            lr_scheduler: None,
+            gradient_clip: None,
+            accumulation_steps: 1,
+            weight_averaging: None,
+            callbacks: vec![],
+            validation: None,
             _marker: PhantomData,
         }
     }
 }
 
 pub struct NetworkBuilderWithOptimizer<A: Activator, Obj: Objective<A>, Opt: Optimizer> {
+    #[allow(clippy::vec_box)]
     layers: Vec<Box<Layer>>,
     objective: Obj,
     optimizer: Opt,
+    rng: StdRng,
+    lr_scheduler: Option<Box<dyn LrScheduler>>,
+    gradient_clip: Option<GradientClip>,
+    accumulation_steps: usize,
+    weight_averaging: Option<WeightAveraging>,
+    callbacks: Vec<Box<dyn Callback<A, Obj, Opt>>>,
+    validation: Option<Validation>,
     _marker: PhantomData<A>,
 }
 
 impl<A: Activator, Obj: Objective<A>, Opt: Optimizer> NetworkBuilderWithOptimizer<A, Obj, Opt> {
+    pub fn schedule_with<S: LrScheduler + 'static>(
+        mut self,
+        lr_scheduler: S,
+    ) -> NetworkBuilderWithOptimizer<A, Obj, Opt> {
+        self.lr_scheduler = Some(Box::new(lr_scheduler));
+        self
+    }
+
+    pub fn clip_gradients(
+        mut self,
+        gradient_clip: GradientClip,
+    ) -> NetworkBuilderWithOptimizer<A, Obj, Opt> {
+        self.gradient_clip = Some(gradient_clip);
+        self
+    }
+
+    // sum gradients over accumulation_steps minibatches before each optimizer step
+    pub fn accumulate_gradients(
+        mut self,
+        accumulation_steps: usize,
+    ) -> NetworkBuilderWithOptimizer<A, Obj, Opt> {
+        self.accumulation_steps = accumulation_steps;
+        self
+    }
+
+    // keep averaged weights over training, see Network::infer_averaged
+    pub fn average_weights(
+        mut self,
+        weight_averaging: WeightAveraging,
+    ) -> NetworkBuilderWithOptimizer<A, Obj, Opt> {
+        self.weight_averaging = Some(weight_averaging);
+        self
+    }
+
+    // called back in order during fit
+    pub fn with_callback<C: Callback<A, Obj, Opt> + 'static>(
+        mut self,
+        callback: C,
+    ) -> NetworkBuilderWithOptimizer<A, Obj, Opt> {
+        self.callbacks.push(Box::new(callback));
+        self
+    }
+
+    // evaluate validation data at the end of every epoch of fit
+    pub fn validate_with(
+        mut self,
+        validation: Validation,
+    ) -> NetworkBuilderWithOptimizer<A, Obj, Opt> {
+        self.validation = Some(validation);
+        self
+    }
+
     pub fn build(self) -> Network<A, Obj, Opt> {
-        Network::new(self.layers, self.objective, self.optimizer)
+        let mut network = Network::new(self.layers, self.objective, self.optimizer);
+        network.set_rng(self.rng);
+        if let Some(lr_scheduler) = self.lr_scheduler {
+            network.set_lr_scheduler(lr_scheduler);
+        }
+        network.set_gradient_clip(self.gradient_clip);
+        network.set_gradient_accumulation(self.accumulation_steps);
+        network.set_weight_averaging(self.weight_averaging);
+        network.set_validation(self.validation);
+        for callback in self.callbacks {
+            network.add_callback(callback);
+        }
+        network
     }
 }
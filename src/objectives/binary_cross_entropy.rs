@@ -3,6 +3,12 @@ use crate::activators::{Activator, Sigmoid};
 
 pub struct BinaryCrossEntropy;
 
+impl Default for BinaryCrossEntropy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl BinaryCrossEntropy {
     pub fn new() -> BinaryCrossEntropy {
         BinaryCrossEntropy {}
@@ -0,0 +1,126 @@
+use super::Objective;
+use crate::activators::{Activator, Sigmoid};
+use crate::functions::log_sigmoid;
+
+// https://arxiv.org/abs/1708.02002
+//
+// binary focal loss on sigmoid of the logit
+// p_t = p if expected is 1 else 1 - p, alpha_t = alpha if expected is 1 else 1 - alpha
+pub struct BinaryFocalLoss {
+    gamma: f64,
+    alpha: Option<f64>,
+}
+
+impl BinaryFocalLoss {
+    // gamma = 0 is the same as BinaryCrossEntropy
+    pub fn new(gamma: f64) -> BinaryFocalLoss {
+        assert!(gamma >= 0., "focal loss gamma should not be negative");
+        BinaryFocalLoss { gamma, alpha: None }
+    }
+
+    // weight of the positive class, 1 - alpha for the negative class
+    pub fn with_alpha(mut self, alpha: f64) -> BinaryFocalLoss {
+        assert!((0. ..=1.).contains(&alpha), "alpha should be in [0, 1]");
+        self.alpha = Some(alpha);
+        self
+    }
+
+    // (sign, alpha_t, ln(p_t)), sign is d(p_t)/d(p)
+    fn target(&self, logit: f64, expected: f64) -> (f64, f64, f64) {
+        if expected < 1e-6 {
+            (-1., self.alpha.map_or(1., |a| 1. - a), log_sigmoid(-logit))
+        } else {
+            (1., self.alpha.unwrap_or(1.), log_sigmoid(logit))
+        }
+    }
+}
+
+impl Objective<Sigmoid> for BinaryFocalLoss {
+    // loss = -alpha_t * (1 - p_t)^gamma * ln(p_t)
+    fn loss(&self, predict: &[Vec<f64>], expected: &[Vec<f64>]) -> Vec<f64> {
+        predict
+            .iter()
+            .zip(expected.iter())
+            .map(|(predict, expected)| {
+                debug_assert_eq!(
+                    expected.len(),
+                    1,
+                    "binary focal loss should have only one dimension"
+                );
+                predict
+                    .iter()
+                    .zip(expected.iter())
+                    .map(|(&logit, &expected)| {
+                        let (_, alpha_t, log_p_t) = self.target(logit, expected);
+                        -(alpha_t * (1. - log_p_t.exp()).powf(self.gamma) * log_p_t)
+                    })
+                    .sum()
+            })
+            .collect()
+    }
+
+    // delta = sign * alpha_t * (gamma * (1-p_t)^gamma * p_t * ln(p_t) - (1-p_t)^(gamma+1))
+    // which is (predict - expected) when gamma = 0 and alpha is not set
+    fn delta_without_deriv(&self, predict: &[Vec<f64>], expected: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        let gamma = self.gamma;
+        predict
+            .iter()
+            .zip(expected.iter())
+            .map(|(predict, expected)| {
+                predict
+                    .iter()
+                    .zip(expected.iter())
+                    .map(|(&logit, &expected)| {
+                        let (sign, alpha_t, log_p_t) = self.target(logit, expected);
+                        let p_t = log_p_t.exp();
+                        let q = 1. - p_t;
+                        sign * alpha_t
+                            * (gamma * q.powf(gamma) * p_t * log_p_t - q.powf(gamma + 1.))
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn predict_from_logits(&self, logits: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        Sigmoid
+            .activate(logits)
+            .iter()
+            .map(|v| v.iter().map(|&v| if v > 0.5 { 1. } else { 0. }).collect())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_binary_focal_loss() {
+        let predict = [vec![2.5], vec![-0.3], vec![40.]];
+        let expected = [vec![0.], vec![1.], vec![1.]];
+
+        let delta = BinaryFocalLoss::new(0.).delta_without_deriv(&predict, &expected);
+        let bce_delta: Vec<Vec<f64>> = Sigmoid
+            .activate(&predict)
+            .iter()
+            .zip(expected.iter())
+            .map(|(p, e)| vec![p[0] - e[0]])
+            .collect();
+        for (delta, bce_delta) in delta.iter().zip(bce_delta.iter()) {
+            assert!((delta[0] - bce_delta[0]).abs() < 1e-12);
+        }
+
+        // compare delta with numerical gradient
+        let focal = BinaryFocalLoss::new(2.).with_alpha(0.25);
+        let delta = focal.delta_without_deriv(&predict, &expected);
+        let h = 1e-6;
+        for (n, (predict, expected)) in predict.iter().zip(expected.iter()).enumerate() {
+            let expected = [expected.clone()];
+            let numerical = (focal.loss(&[vec![predict[0] + h]], &expected)[0]
+                - focal.loss(&[vec![predict[0] - h]], &expected)[0])
+                / (2. * h);
+            assert!((delta[n][0] - numerical).abs() < 1e-6);
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use super::{nearest_neighbour_hits, Objective};
+use crate::activators::Linear;
+use crate::functions::euclidean_distance;
+
+// http://yann.lecun.com/exdb/publis/pdf/hadsell-chopra-lecun-06.pdf
+//
+// predictions are embeddings and labels are class ids, eg. vec![3.]
+// every pair (i, j) in the minibatch contributes
+//     same label:      0.5 * d(i, j)^2
+//     different label: 0.5 * max(0, margin - d(i, j))^2
+// each sample gets its share of the pairs it is in, so the mean loss of the
+// minibatch is the mean loss of its pairs
+// with sample weights a pair is weighted by the mean weight of its samples,
+// masks are not supported
+pub struct Contrastive {
+    margin: f64,
+}
+
+impl Contrastive {
+    pub fn new(margin: f64) -> Contrastive {
+        Contrastive { margin }
+    }
+
+    // (pair loss, d(i, j), d(pair loss)/d(d(i, j)))
+    fn pair(&self, a: &[f64], b: &[f64], same: bool) -> (f64, f64, f64) {
+        let distance = euclidean_distance(a, b);
+        if same {
+            (0.5 * distance * distance, distance, distance)
+        } else {
+            let gap = (self.margin - distance).max(0.);
+            (0.5 * gap * gap, distance, -gap)
+        }
+    }
+
+    fn losses(&self, predict: &[Vec<f64>], expected: &[Vec<f64>], weights: &[f64]) -> Vec<f64> {
+        let n = predict.len();
+        if n < 2 {
+            return vec![0.; n];
+        }
+        (0..n)
+            .map(|i| {
+                (0..n)
+                    .filter(|&j| j != i)
+                    .map(|j| {
+                        self.pair(&predict[i], &predict[j], expected[i] == expected[j])
+                            .0
+                            * (weights[i] + weights[j])
+                            / 2.
+                    })
+                    .sum::<f64>()
+                    / (n - 1) as f64
+            })
+            .collect()
+    }
+
+    // d(pair loss)/d(predict(i)) = d(pair loss)/d(d) * (predict(i) - predict(j)) / d
+    fn deltas(
+        &self,
+        predict: &[Vec<f64>],
+        expected: &[Vec<f64>],
+        weights: &[f64],
+    ) -> Vec<Vec<f64>> {
+        let n = predict.len();
+        let mut deltas = vec![vec![0.; predict.first().map_or(0, Vec::len)]; n];
+        if n < 2 {
+            return deltas;
+        }
+        for i in 0..n {
+            for j in (i + 1)..n {
+                // every pair is shared by both samples, so it counts for both weights
+                let scale = (weights[i] + weights[j]) / (n - 1) as f64;
+                let (_, distance, deriv) =
+                    self.pair(&predict[i], &predict[j], expected[i] == expected[j]);
+                if distance < 1e-12 {
+                    continue;
+                }
+                for k in 0..predict[i].len() {
+                    let grad = scale * deriv * (predict[i][k] - predict[j][k]) / distance;
+                    deltas[i][k] += grad;
+                    deltas[j][k] -= grad;
+                }
+            }
+        }
+        deltas
+    }
+}
+
+impl Objective<Linear> for Contrastive {
+    fn loss(&self, predict: &[Vec<f64>], expected: &[Vec<f64>]) -> Vec<f64> {
+        self.losses(predict, expected, &vec![1.; predict.len()])
+    }
+
+    fn delta_without_deriv(&self, predict: &[Vec<f64>], expected: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        self.deltas(predict, expected, &vec![1.; predict.len()])
+    }
+
+    fn weighted_loss(
+        &self,
+        predict: &[Vec<f64>],
+        expected: &[Vec<f64>],
+        weights: &[f64],
+        masks: Option<&[Vec<f64>]>,
+    ) -> Vec<f64> {
+        assert!(masks.is_none(), "contrastive loss doesn't support masks");
+        self.losses(predict, expected, weights)
+    }
+
+    fn weighted_delta_without_deriv(
+        &self,
+        predict: &[Vec<f64>],
+        expected: &[Vec<f64>],
+        weights: &[f64],
+        masks: Option<&[Vec<f64>]>,
+    ) -> Vec<Vec<f64>> {
+        assert!(masks.is_none(), "contrastive loss doesn't support masks");
+        self.deltas(predict, expected, weights)
+    }
+
+    // labels are class ids
+    fn is_valid_label(&self, expected: &[f64], _: usize) -> bool {
+        expected.len() == 1
+    }
+
+    fn predict_from_logits(&self, logits: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        logits.to_owned()
+    }
+
+    fn hits(&self, logits: &[Vec<f64>], expected: &[Vec<f64>]) -> Vec<bool> {
+        nearest_neighbour_hits(logits, expected, euclidean_distance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contrastive() {
+        let predict = [vec![0., 1.], vec![0.5, 0.8], vec![1., -0.2], vec![0.9, 0.]];
+        let expected = [vec![0.], vec![0.], vec![1.], vec![1.]];
+        // uneven sample weights, a pair counts for both of its samples
+        let weights = [1., 2., 0.5, 1.5];
+        let contrastive = Contrastive::new(2.);
+
+        let delta = contrastive.weighted_delta_without_deriv(&predict, &expected, &weights, None);
+        let h = 1e-6;
+        for i in 0..predict.len() {
+            for k in 0..2 {
+                let (mut plus, mut minus) = (predict.to_vec(), predict.to_vec());
+                plus[i][k] += h;
+                minus[i][k] -= h;
+                let numerical = (contrastive
+                    .weighted_loss(&plus, &expected, &weights, None)
+                    .iter()
+                    .sum::<f64>()
+                    - contrastive
+                        .weighted_loss(&minus, &expected, &weights, None)
+                        .iter()
+                        .sum::<f64>())
+                    / (2. * h);
+                assert!((delta[i][k] - numerical).abs() < 1e-6);
+            }
+        }
+        assert_eq!(
+            contrastive.hits(&predict, &expected),
+            [true, true, true, true]
+        );
+    }
+}
@@ -0,0 +1,185 @@
+use super::{nearest_neighbour_hits, Objective};
+use crate::activators::Linear;
+use crate::functions::cosine_similarity;
+
+// predictions are embeddings and labels are class ids, eg. vec![3.]
+// every pair (i, j) in the minibatch contributes
+//     same label:      1 - cos(i, j)
+//     different label: max(0, cos(i, j) - margin)
+// each sample gets its share of the pairs it is in, so the mean loss of the
+// minibatch is the mean loss of its pairs
+// with sample weights a pair is weighted by the mean weight of its samples,
+// masks are not supported
+pub struct CosineEmbedding {
+    margin: f64,
+}
+
+impl CosineEmbedding {
+    pub fn new(margin: f64) -> CosineEmbedding {
+        assert!(
+            (-1. ..=1.).contains(&margin),
+            "cosine embedding margin should be in [-1, 1]"
+        );
+        CosineEmbedding { margin }
+    }
+
+    // (pair loss, d(pair loss)/d(cos(i, j)))
+    fn pair(&self, a: &[f64], b: &[f64], same: bool) -> (f64, f64) {
+        let cos = cosine_similarity(a, b);
+        if same {
+            (1. - cos, -1.)
+        } else if cos > self.margin {
+            (cos - self.margin, 1.)
+        } else {
+            (0., 0.)
+        }
+    }
+
+    fn losses(&self, predict: &[Vec<f64>], expected: &[Vec<f64>], weights: &[f64]) -> Vec<f64> {
+        let n = predict.len();
+        if n < 2 {
+            return vec![0.; n];
+        }
+        (0..n)
+            .map(|i| {
+                (0..n)
+                    .filter(|&j| j != i)
+                    .map(|j| {
+                        self.pair(&predict[i], &predict[j], expected[i] == expected[j])
+                            .0
+                            * (weights[i] + weights[j])
+                            / 2.
+                    })
+                    .sum::<f64>()
+                    / (n - 1) as f64
+            })
+            .collect()
+    }
+
+    fn deltas(
+        &self,
+        predict: &[Vec<f64>],
+        expected: &[Vec<f64>],
+        weights: &[f64],
+    ) -> Vec<Vec<f64>> {
+        let n = predict.len();
+        let mut deltas = vec![vec![0.; predict.first().map_or(0, Vec::len)]; n];
+        if n < 2 {
+            return deltas;
+        }
+        for i in 0..n {
+            for j in (i + 1)..n {
+                // every pair is shared by both samples, so it counts for both weights
+                let scale = (weights[i] + weights[j]) / (n - 1) as f64;
+                let (_, deriv) = self.pair(&predict[i], &predict[j], expected[i] == expected[j]);
+                if deriv == 0. {
+                    continue;
+                }
+                let deriv_i = cosine_similarity_deriv(&predict[i], &predict[j]);
+                let deriv_j = cosine_similarity_deriv(&predict[j], &predict[i]);
+                for k in 0..predict[i].len() {
+                    deltas[i][k] += scale * deriv * deriv_i[k];
+                    deltas[j][k] += scale * deriv * deriv_j[k];
+                }
+            }
+        }
+        deltas
+    }
+}
+
+// d(cos(a, b))/d(a) = b / (|a| * |b|) - cos(a, b) * a / |a|^2
+fn cosine_similarity_deriv(a: &[f64], b: &[f64]) -> Vec<f64> {
+    let norm = |v: &[f64]| v.iter().map(|v| v * v).sum::<f64>().sqrt().max(1e-12);
+    let (norm_a, norm_b) = (norm(a), norm(b));
+    let cos = cosine_similarity(a, b);
+    a.iter()
+        .zip(b.iter())
+        .map(|(a, b)| b / (norm_a * norm_b) - cos * a / (norm_a * norm_a))
+        .collect()
+}
+
+impl Objective<Linear> for CosineEmbedding {
+    fn loss(&self, predict: &[Vec<f64>], expected: &[Vec<f64>]) -> Vec<f64> {
+        self.losses(predict, expected, &vec![1.; predict.len()])
+    }
+
+    fn delta_without_deriv(&self, predict: &[Vec<f64>], expected: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        self.deltas(predict, expected, &vec![1.; predict.len()])
+    }
+
+    fn weighted_loss(
+        &self,
+        predict: &[Vec<f64>],
+        expected: &[Vec<f64>],
+        weights: &[f64],
+        masks: Option<&[Vec<f64>]>,
+    ) -> Vec<f64> {
+        assert!(
+            masks.is_none(),
+            "cosine embedding loss doesn't support masks"
+        );
+        self.losses(predict, expected, weights)
+    }
+
+    fn weighted_delta_without_deriv(
+        &self,
+        predict: &[Vec<f64>],
+        expected: &[Vec<f64>],
+        weights: &[f64],
+        masks: Option<&[Vec<f64>]>,
+    ) -> Vec<Vec<f64>> {
+        assert!(
+            masks.is_none(),
+            "cosine embedding loss doesn't support masks"
+        );
+        self.deltas(predict, expected, weights)
+    }
+
+    // labels are class ids
+    fn is_valid_label(&self, expected: &[f64], _: usize) -> bool {
+        expected.len() == 1
+    }
+
+    fn predict_from_logits(&self, logits: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        logits.to_owned()
+    }
+
+    fn hits(&self, logits: &[Vec<f64>], expected: &[Vec<f64>]) -> Vec<bool> {
+        nearest_neighbour_hits(logits, expected, |a, b| -cosine_similarity(a, b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cosine_embedding() {
+        let predict = [vec![0., 1.], vec![0.5, 0.8], vec![1., -0.2], vec![0.9, 0.3]];
+        let expected = [vec![0.], vec![0.], vec![1.], vec![1.]];
+        // uneven sample weights, a pair counts for both of its samples
+        let weights = [1., 2., 0.5, 1.5];
+        let cosine = CosineEmbedding::new(0.1);
+
+        let delta = cosine.weighted_delta_without_deriv(&predict, &expected, &weights, None);
+        let h = 1e-6;
+        for i in 0..predict.len() {
+            for k in 0..2 {
+                let (mut plus, mut minus) = (predict.to_vec(), predict.to_vec());
+                plus[i][k] += h;
+                minus[i][k] -= h;
+                let numerical = (cosine
+                    .weighted_loss(&plus, &expected, &weights, None)
+                    .iter()
+                    .sum::<f64>()
+                    - cosine
+                        .weighted_loss(&minus, &expected, &weights, None)
+                        .iter()
+                        .sum::<f64>())
+                    / (2. * h);
+                assert!((delta[i][k] - numerical).abs() < 1e-6);
+            }
+        }
+        assert_eq!(cosine.hits(&predict, &expected), [true, true, true, true]);
+    }
+}
@@ -1,27 +1,74 @@
-use super::Objective;
-use crate::activators::{Activator, Softmax};
-use crate::functions::{argmax, into_onehot};
+use super::{class_hits, dense_target, is_onehot_or_sparse, onehot_predictions, Objective};
+use crate::activators::Softmax;
+use crate::functions::log_softmax;
 
-pub struct CrossEntropy;
+// labels can be onehot (or soft) vectors, or sparse vectors holding only the
+// class index, eg. vec![3.] instead of into_onehot(3, classes)
+pub struct CrossEntropy {
+    label_smoothing: f64,
+    class_weights: Option<Vec<f64>>,
+}
+
+impl Default for CrossEntropy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl CrossEntropy {
     pub fn new() -> CrossEntropy {
-        CrossEntropy {}
+        CrossEntropy {
+            label_smoothing: 0.,
+            class_weights: None,
+        }
+    }
+
+    // expected = (1 - epsilon) * expected + epsilon / classes
+    pub fn with_label_smoothing(mut self, epsilon: f64) -> CrossEntropy {
+        assert!(
+            (0. ..1.).contains(&epsilon),
+            "label smoothing should be in [0, 1)"
+        );
+        self.label_smoothing = epsilon;
+        self
+    }
+
+    // scale each class's contribution to the loss, eg. inverse class frequency
+    // for imbalanced data
+    pub fn with_class_weights(mut self, class_weights: Vec<f64>) -> CrossEntropy {
+        self.class_weights = Some(class_weights);
+        self
+    }
+
+    // weight(i) * smoothed expected(i)
+    fn weighted_target(&self, expected: &[f64], classes: usize) -> Vec<f64> {
+        let epsilon = self.label_smoothing;
+        dense_target(expected, classes)
+            .into_iter()
+            .enumerate()
+            .map(|(i, expected)| {
+                let weight = self.class_weights.as_ref().map_or(1., |weights| {
+                    debug_assert_eq!(weights.len(), classes, "one weight per class");
+                    weights[i]
+                });
+                weight * ((1. - epsilon) * expected + epsilon / classes as f64)
+            })
+            .collect()
     }
 }
 
 impl Objective<Softmax> for CrossEntropy {
-    // loss = -SUM(expected(i) * ln(Softmax(predict(i))))
+    // loss = -SUM(weight(i) * expected(i) * log_softmax(predict(i)))
+    // log_softmax uses log-sum-exp, so it stays finite when softmax underflows to 0
     fn loss(&self, predict: &[Vec<f64>], expected: &[Vec<f64>]) -> Vec<f64> {
-        Softmax
-            .activate(predict)
+        predict
             .iter()
             .zip(expected.iter())
             .map(|(predict, expected)| {
-                predict
+                log_softmax(predict)
                     .iter()
-                    .zip(expected.iter())
-                    .map(|(predict, expected)| -(expected * predict.ln()))
+                    .zip(self.weighted_target(expected, predict.len()).iter())
+                    .map(|(log_predict, target)| -(target * log_predict))
                     .sum()
             })
             .collect()
@@ -34,24 +81,78 @@ impl Objective<Softmax> for CrossEntropy {
         // for i: 0-n
         //     if i == j, expected=1: delta = (predict-1) <- (predict-expected)
         //     if i != j, expected=0: delta = predict     <- (predict-expected)
-        Softmax
-            .activate(predict)
+        //
+        // with weighted target t(i) = weight(i) * expected(i) it generalizes to
+        //     delta = predict * SUM(t) - t
+        predict
             .iter()
             .zip(expected.iter())
             .map(|(predict, expected)| {
-                predict
+                let target = self.weighted_target(expected, predict.len());
+                let sum_target: f64 = target.iter().sum();
+                log_softmax(predict)
                     .iter()
-                    .zip(expected.iter())
-                    .map(|(predict, expected)| predict - expected)
+                    .zip(target.iter())
+                    .map(|(log_predict, target)| log_predict.exp() * sum_target - target)
                     .collect()
             })
             .collect()
     }
 
+    fn is_valid_label(&self, expected: &[f64], num_outputs: usize) -> bool {
+        is_onehot_or_sparse(expected, num_outputs)
+    }
+
     fn predict_from_logits(&self, logits: &[Vec<f64>]) -> Vec<Vec<f64>> {
-        logits
-            .iter()
-            .map(|logits| into_onehot(argmax(&logits), logits.len()))
-            .collect()
+        onehot_predictions(logits)
+    }
+
+    fn hits(&self, logits: &[Vec<f64>], expected: &[Vec<f64>]) -> Vec<bool> {
+        class_hits(logits, expected)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cross_entropy_stable() {
+        let predict = [vec![1000., 2000., 3000.]];
+        let loss = CrossEntropy::new().loss(&predict, &[vec![1., 0., 0.]]);
+        assert_eq!(loss, [2000.]);
+
+        let delta = CrossEntropy::new().delta_without_deriv(&predict, &[vec![1., 0., 0.]]);
+        assert_eq!(delta, [[-1., 0., 1.]]);
+    }
+
+    #[test]
+    fn test_cross_entropy_sparse() {
+        let predict = [vec![1., 2., 3.], vec![3., 1., 2.]];
+        let onehot = [vec![0., 1., 0.], vec![1., 0., 0.]];
+        let sparse = [vec![1.], vec![0.]];
+        let objective = CrossEntropy::new()
+            .with_label_smoothing(0.1)
+            .with_class_weights(vec![1., 2., 3.]);
+
+        assert_eq!(
+            objective.loss(&predict, &onehot),
+            objective.loss(&predict, &sparse)
+        );
+        assert_eq!(
+            objective.delta_without_deriv(&predict, &onehot),
+            objective.delta_without_deriv(&predict, &sparse)
+        );
+        assert_eq!(objective.hits(&predict, &sparse), [false, true]);
+    }
+
+    #[test]
+    fn test_cross_entropy_label_smoothing() {
+        let predict = [vec![1., 2., 3.]];
+        let smoothed = CrossEntropy::new()
+            .with_label_smoothing(0.3)
+            .loss(&predict, &[vec![0., 0., 1.]]);
+        let soft = CrossEntropy::new().loss(&predict, &[vec![0.1, 0.1, 0.8]]);
+        assert!((smoothed[0] - soft[0]).abs() < 1e-12);
     }
 }
@@ -0,0 +1,146 @@
+use super::{class_hits, onehot_predictions, CrossEntropy, KLDivergence, Objective};
+use crate::activators::{Activator, Softmax};
+use crate::functions::softmax;
+use crate::network::Network;
+use crate::optimizers::Optimizer;
+
+// https://arxiv.org/abs/1503.02531
+//
+// loss = alpha * T^2 * KL(softmax(teacher / T) || softmax(predict / T))
+//      + (1 - alpha) * CrossEntropy(hard label, predict)
+//
+// expected is the hard label (onehot or sparse) followed by the teacher's
+// logits, see Distillation::targets_with_teacher_logits
+pub struct Distillation {
+    temperature: f64,
+    alpha: f64,
+}
+
+impl Distillation {
+    pub fn new(temperature: f64, alpha: f64) -> Distillation {
+        assert!(temperature > 0., "temperature should be positive");
+        assert!((0. ..=1.).contains(&alpha), "alpha should be in [0, 1]");
+        Distillation { temperature, alpha }
+    }
+
+    // runs the teacher over inputs and appends its logits to the hard labels
+    pub fn targets_with_teacher_logits<A: Activator, Obj: Objective<A>, Opt: Optimizer>(
+        teacher: &mut Network<A, Obj, Opt>,
+        inputs: &[Vec<f64>],
+        hard_labels: &[Vec<f64>],
+    ) -> Vec<Vec<f64>> {
+        teacher
+            .logits(inputs)
+            .into_iter()
+            .zip(hard_labels.iter())
+            .map(|(logits, hard_label)| {
+                let mut target = hard_label.clone();
+                target.extend(logits);
+                target
+            })
+            .collect()
+    }
+
+    // (hard label, teacher's softmax(logits / T)), predict(i) / T
+    fn split(&self, predict: &[f64], expected: &[f64]) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
+        let classes = predict.len();
+        debug_assert!(
+            expected.len() == 2 * classes || expected.len() == 1 + classes,
+            "expected should be hard label followed by teacher logits"
+        );
+        let (hard_label, teacher_logits) = expected.split_at(expected.len() - classes);
+        let scale = |logits: &[f64]| -> Vec<f64> {
+            logits
+                .iter()
+                .map(|logit| logit / self.temperature)
+                .collect()
+        };
+        (
+            hard_label.to_vec(),
+            softmax(&scale(teacher_logits)),
+            scale(predict),
+        )
+    }
+}
+
+impl Objective<Softmax> for Distillation {
+    fn loss(&self, predict: &[Vec<f64>], expected: &[Vec<f64>]) -> Vec<f64> {
+        let t = self.temperature;
+        predict
+            .iter()
+            .zip(expected.iter())
+            .map(|(predict, expected)| {
+                let (hard_label, soft_label, scaled_predict) = self.split(predict, expected);
+                let soft_loss = KLDivergence.loss(&[scaled_predict], &[soft_label])[0];
+                let hard_loss =
+                    CrossEntropy::new().loss(std::slice::from_ref(predict), &[hard_label])[0];
+                self.alpha * t * t * soft_loss + (1. - self.alpha) * hard_loss
+            })
+            .collect()
+    }
+
+    // delta = alpha * T * (softmax(predict / T) - soft label)
+    //       + (1 - alpha) * (softmax(predict) - hard label)
+    fn delta_without_deriv(&self, predict: &[Vec<f64>], expected: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        let t = self.temperature;
+        predict
+            .iter()
+            .zip(expected.iter())
+            .map(|(predict, expected)| {
+                let (hard_label, soft_label, scaled_predict) = self.split(predict, expected);
+                let soft_delta = KLDivergence.delta_without_deriv(&[scaled_predict], &[soft_label]);
+                let hard_delta = CrossEntropy::new()
+                    .delta_without_deriv(std::slice::from_ref(predict), &[hard_label]);
+                soft_delta[0]
+                    .iter()
+                    .zip(hard_delta[0].iter())
+                    .map(|(soft, hard)| self.alpha * t * soft + (1. - self.alpha) * hard)
+                    .collect()
+            })
+            .collect()
+    }
+
+    // hard label, onehot or sparse, followed by the teacher's logits
+    fn is_valid_label(&self, expected: &[f64], num_outputs: usize) -> bool {
+        expected.len() == 2 * num_outputs || expected.len() == 1 + num_outputs
+    }
+
+    fn predict_from_logits(&self, logits: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        onehot_predictions(logits)
+    }
+
+    fn hits(&self, logits: &[Vec<f64>], expected: &[Vec<f64>]) -> Vec<bool> {
+        // the hard label is in front of the teacher's
+        let hard_labels: Vec<&[f64]> = logits
+            .iter()
+            .zip(expected.iter())
+            .map(|(logits, expected)| &expected[..expected.len() - logits.len()])
+            .collect();
+        class_hits(logits, &hard_labels)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distillation() {
+        let predict = [vec![1., 2., 0.5]];
+        let expected = [vec![2., 0.3, 1.5, -1.]];
+
+        let distillation = Distillation::new(2., 0.7);
+        let delta = distillation.delta_without_deriv(&predict, &expected);
+        let h = 1e-6;
+        for i in 0..3 {
+            let (mut plus, mut minus) = (predict[0].clone(), predict[0].clone());
+            plus[i] += h;
+            minus[i] -= h;
+            let numerical = (distillation.loss(&[plus], &expected)[0]
+                - distillation.loss(&[minus], &expected)[0])
+                / (2. * h);
+            assert!((delta[0][i] - numerical).abs() < 1e-6);
+        }
+        assert_eq!(distillation.hits(&predict, &expected), [false]);
+    }
+}
@@ -0,0 +1,143 @@
+use super::{class_hits, dense_target, is_onehot_or_sparse, onehot_predictions, Objective};
+use crate::activators::Softmax;
+use crate::functions::log_softmax;
+
+// https://arxiv.org/abs/1708.02002
+//
+// multi-class focal loss on softmax of the logits, down-weights well classified
+// examples so training focuses on the hard (usually minority) ones
+// labels can be onehot (or soft) or sparse, same as CrossEntropy
+pub struct FocalLoss {
+    gamma: f64,
+    alpha: Option<Vec<f64>>,
+}
+
+impl FocalLoss {
+    // gamma = 0 is the same as CrossEntropy
+    pub fn new(gamma: f64) -> FocalLoss {
+        assert!(gamma >= 0., "focal loss gamma should not be negative");
+        FocalLoss { gamma, alpha: None }
+    }
+
+    // per-class balancing weights
+    pub fn with_alpha(mut self, alpha: Vec<f64>) -> FocalLoss {
+        self.alpha = Some(alpha);
+        self
+    }
+
+    fn alpha(&self, class: usize, classes: usize) -> f64 {
+        self.alpha.as_ref().map_or(1., |alpha| {
+            debug_assert_eq!(alpha.len(), classes, "one alpha per class");
+            alpha[class]
+        })
+    }
+}
+
+impl Objective<Softmax> for FocalLoss {
+    // loss = -SUM(alpha(i) * expected(i) * (1 - p(i))^gamma * ln(p(i)))
+    fn loss(&self, predict: &[Vec<f64>], expected: &[Vec<f64>]) -> Vec<f64> {
+        predict
+            .iter()
+            .zip(expected.iter())
+            .map(|(predict, expected)| {
+                let classes = predict.len();
+                log_softmax(predict)
+                    .iter()
+                    .zip(dense_target(expected, classes).iter())
+                    .enumerate()
+                    .map(|(i, (log_p, target))| {
+                        -(self.alpha(i, classes)
+                            * target
+                            * (1. - log_p.exp()).powf(self.gamma)
+                            * log_p)
+                    })
+                    .sum()
+            })
+            .collect()
+    }
+
+    // with g(i) = alpha(i) * expected(i) * (gamma * (1-p(i))^(gamma-1) * p(i) * ln(p(i)) - (1-p(i))^gamma)
+    // delta(i) = g(i) - p(i) * SUM(g)
+    // which is (predict - expected) when gamma = 0 and alpha = 1
+    fn delta_without_deriv(&self, predict: &[Vec<f64>], expected: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        let gamma = self.gamma;
+        predict
+            .iter()
+            .zip(expected.iter())
+            .map(|(predict, expected)| {
+                let classes = predict.len();
+                let log_ps = log_softmax(predict);
+                let gs: Vec<f64> = log_ps
+                    .iter()
+                    .zip(dense_target(expected, classes).iter())
+                    .enumerate()
+                    .map(|(i, (&log_p, target))| {
+                        let p = log_p.exp();
+                        let q = 1. - p;
+                        // (1-p)^(gamma-1) * p * ln(p) -> 0 when p -> 1
+                        let focal_deriv = if q > 0. {
+                            gamma * q.powf(gamma - 1.) * p * log_p
+                        } else {
+                            0.
+                        };
+                        self.alpha(i, classes) * target * (focal_deriv - q.powf(gamma))
+                    })
+                    .collect();
+                let sum_g: f64 = gs.iter().sum();
+                log_ps
+                    .iter()
+                    .zip(gs.iter())
+                    .map(|(log_p, g)| g - log_p.exp() * sum_g)
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn is_valid_label(&self, expected: &[f64], num_outputs: usize) -> bool {
+        is_onehot_or_sparse(expected, num_outputs)
+    }
+
+    fn predict_from_logits(&self, logits: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        onehot_predictions(logits)
+    }
+
+    fn hits(&self, logits: &[Vec<f64>], expected: &[Vec<f64>]) -> Vec<bool> {
+        class_hits(logits, expected)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objectives::CrossEntropy;
+
+    #[test]
+    fn test_focal_loss() {
+        let predict = [vec![1., 2., 3.], vec![0.5, -1., 4.]];
+        let expected = [vec![1.], vec![2.]];
+
+        let focal = FocalLoss::new(0.);
+        let ce = CrossEntropy::new();
+        assert_eq!(
+            focal.loss(&predict, &expected),
+            ce.loss(&predict, &expected)
+        );
+
+        // compare delta with numerical gradient
+        let focal = FocalLoss::new(2.).with_alpha(vec![0.25, 0.5, 0.75]);
+        let delta = focal.delta_without_deriv(&predict, &expected);
+        let h = 1e-6;
+        for (n, (predict, expected)) in predict.iter().zip(expected.iter()).enumerate() {
+            for i in 0..predict.len() {
+                let (mut plus, mut minus) = (predict.clone(), predict.clone());
+                plus[i] += h;
+                minus[i] -= h;
+                let expected = [expected.clone()];
+                let numerical = (focal.loss(&[plus], &expected)[0]
+                    - focal.loss(&[minus], &expected)[0])
+                    / (2. * h);
+                assert!((delta[n][i] - numerical).abs() < 1e-6);
+            }
+        }
+    }
+}
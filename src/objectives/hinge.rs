@@ -0,0 +1,133 @@
+use super::Objective;
+use crate::activators::Linear;
+
+// binary hinge loss of a linear svm, labels are 0/1 (or -1/1) and mapped to y = -1/1
+// loss = max(0, 1 - y * predict), or its square
+pub struct Hinge {
+    squared: bool,
+}
+
+impl Default for Hinge {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hinge {
+    pub fn new() -> Hinge {
+        Hinge { squared: false }
+    }
+
+    // squared hinge is smooth and penalizes margin violations quadratically
+    pub fn with_squared(mut self) -> Hinge {
+        self.squared = true;
+        self
+    }
+
+    fn sign(expected: f64) -> f64 {
+        if expected < 1e-6 {
+            -1.
+        } else {
+            1.
+        }
+    }
+}
+
+impl Objective<Linear> for Hinge {
+    fn loss(&self, predict: &[Vec<f64>], expected: &[Vec<f64>]) -> Vec<f64> {
+        predict
+            .iter()
+            .zip(expected.iter())
+            .map(|(predict, expected)| {
+                debug_assert_eq!(expected.len(), 1, "hinge should have only one dimension");
+                predict
+                    .iter()
+                    .zip(expected.iter())
+                    .map(|(predict, &expected)| {
+                        let margin = (1. - Hinge::sign(expected) * predict).max(0.);
+                        if self.squared {
+                            margin * margin
+                        } else {
+                            margin
+                        }
+                    })
+                    .sum()
+            })
+            .collect()
+    }
+
+    // delta = -y when margin violated, -2 * y * margin for squared hinge
+    fn delta_without_deriv(&self, predict: &[Vec<f64>], expected: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        predict
+            .iter()
+            .zip(expected.iter())
+            .map(|(predict, expected)| {
+                predict
+                    .iter()
+                    .zip(expected.iter())
+                    .map(|(predict, &expected)| {
+                        let y = Hinge::sign(expected);
+                        let margin = 1. - y * predict;
+                        if margin <= 0. {
+                            0.
+                        } else if self.squared {
+                            -2. * y * margin
+                        } else {
+                            -y
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn predict_from_logits(&self, logits: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        logits
+            .iter()
+            .map(|v| v.iter().map(|&v| if v > 0. { 1. } else { 0. }).collect())
+            .collect()
+    }
+
+    // compares signs, as labels can be -1 where predictions are 0
+    fn hits(&self, logits: &[Vec<f64>], expected: &[Vec<f64>]) -> Vec<bool> {
+        logits
+            .iter()
+            .zip(expected.iter())
+            .map(|(logits, expected)| {
+                logits
+                    .iter()
+                    .zip(expected.iter())
+                    .all(|(&logit, &expected)| (logit > 0.) == (Hinge::sign(expected) > 0.))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hinge() {
+        let predict = [vec![0.5], vec![-2.], vec![0.25]];
+        let expected = [vec![1.], vec![0.], vec![0.]];
+
+        let hinge = Hinge::new();
+        assert_eq!(hinge.loss(&predict, &expected), [0.5, 0., 1.25]);
+        assert_eq!(hinge.hits(&predict, &expected), [true, true, false]);
+        // -1 labels hit by negative logits too
+        assert_eq!(hinge.hits(&[vec![-2.]], &[vec![-1.]]), [true]);
+        assert_eq!(
+            hinge.delta_without_deriv(&predict, &expected),
+            [[-1.], [0.], [1.]]
+        );
+
+        let hinge = Hinge::new().with_squared();
+        assert_eq!(hinge.loss(&predict, &expected), [0.25, 0., 1.5625]);
+        assert_eq!(
+            hinge.delta_without_deriv(&predict, &expected),
+            [[-1.], [0.], [2.5]]
+        );
+        assert_eq!(hinge.predict_from_logits(&predict), [[1.], [0.], [1.]]);
+    }
+}
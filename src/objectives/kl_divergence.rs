@@ -0,0 +1,105 @@
+use super::{class_hits, onehot_predictions, Objective};
+use crate::activators::{Activator, Softmax};
+use crate::functions::{log_softmax, softmax};
+use crate::network::Network;
+use crate::optimizers::Optimizer;
+
+// KL(expected || softmax(predict)), expected are soft targets which sum to 1
+pub struct KLDivergence;
+
+impl Default for KLDivergence {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl KLDivergence {
+    pub fn new() -> KLDivergence {
+        KLDivergence {}
+    }
+
+    // soft targets = softmax(teacher logits / temperature)
+    pub fn soft_targets<A: Activator, Obj: Objective<A>, Opt: Optimizer>(
+        teacher: &mut Network<A, Obj, Opt>,
+        inputs: &[Vec<f64>],
+        temperature: f64,
+    ) -> Vec<Vec<f64>> {
+        teacher
+            .logits(inputs)
+            .iter()
+            .map(|logits| {
+                softmax(
+                    &logits
+                        .iter()
+                        .map(|logit| logit / temperature)
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect()
+    }
+}
+
+impl Objective<Softmax> for KLDivergence {
+    // loss = SUM(expected(i) * (ln(expected(i)) - log_softmax(predict(i))))
+    fn loss(&self, predict: &[Vec<f64>], expected: &[Vec<f64>]) -> Vec<f64> {
+        predict
+            .iter()
+            .zip(expected.iter())
+            .map(|(predict, expected)| {
+                debug_assert_eq!(predict.len(), expected.len());
+                log_softmax(predict)
+                    .iter()
+                    .zip(expected.iter())
+                    .filter(|(_, &expected)| expected > 0.)
+                    .map(|(log_predict, expected)| expected * (expected.ln() - log_predict))
+                    .sum()
+            })
+            .collect()
+    }
+
+    // same as CrossEntropy, as they only differ in the entropy of expected
+    fn delta_without_deriv(&self, predict: &[Vec<f64>], expected: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        Softmax
+            .activate(predict)
+            .iter()
+            .zip(expected.iter())
+            .map(|(predict, expected)| {
+                predict
+                    .iter()
+                    .zip(expected.iter())
+                    .map(|(predict, expected)| predict - expected)
+                    .collect()
+            })
+            .collect()
+    }
+
+    // onehot of the most likely class, same as CrossEntropy, the probabilities
+    // are Softmax.activate(logits)
+    fn predict_from_logits(&self, logits: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        onehot_predictions(logits)
+    }
+
+    fn hits(&self, logits: &[Vec<f64>], expected: &[Vec<f64>]) -> Vec<bool> {
+        class_hits(logits, expected)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kl_divergence() {
+        let soft_label = softmax(&[0.3, 1.5, -1.]);
+        let loss = KLDivergence.loss(
+            &[vec![0.3, 1.5, -1.], vec![1., 2., 3.]],
+            &[soft_label.clone(), soft_label],
+        );
+        assert!(loss[0].abs() < 1e-12);
+        assert!(loss[1] > 0.);
+        assert_eq!(
+            KLDivergence.predict_from_logits(&[vec![0.3, 1.5, -1.]]),
+            [[0., 1., 0.]]
+        );
+    }
+}
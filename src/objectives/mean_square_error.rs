@@ -3,6 +3,12 @@ use crate::activators::{Activator, Sigmoid};
 
 pub struct MeanSquareError;
 
+impl Default for MeanSquareError {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl MeanSquareError {
     pub fn new() -> MeanSquareError {
         MeanSquareError {}
@@ -0,0 +1,301 @@
+use super::Objective;
+use crate::activators::Linear;
+use crate::functions::{argmax, log_softmax, softmax, standard_normal};
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cell::RefCell;
+use std::f64::consts::PI;
+
+// what predict_from_logits (and so Network::infer) returns for a mixture
+pub enum MixturePrediction {
+    // SUM(weight(k) * mean(k))
+    Mean,
+    // mean of the heaviest component, which approximates the mode
+    Mode,
+    // one sample drawn from the mixture
+    Sample,
+}
+
+// http://publications.aston.ac.uk/id/eprint/373/1/NCRG_94_004.pdf
+//
+// mixture density network, the logits of output layer are K gaussian components
+// with diagonal covariance over a D dimension target, laid out as
+//     [weight logits (K) | means (K * D) | log stddevs (K * D)]
+// so output layer needs K * (2 * D + 1) nodes, trained by negative log-likelihood
+pub struct MixtureDensity {
+    components: usize,
+    dim: usize,
+    prediction: MixturePrediction,
+    tolerance: Option<f64>,
+    // draws MixturePrediction::Sample, predict_from_logits takes &self
+    rng: RefCell<StdRng>,
+}
+
+impl MixtureDensity {
+    // components: K gaussian components
+    // dim: D dimensions of the target
+    pub fn new(components: usize, dim: usize) -> MixtureDensity {
+        assert!(components > 0, "mixture needs at least one component");
+        assert!(dim > 0, "target needs at least one dimension");
+        MixtureDensity {
+            components,
+            dim,
+            prediction: MixturePrediction::Mean,
+            tolerance: None,
+            rng: RefCell::new(StdRng::from_entropy()),
+        }
+    }
+
+    pub fn with_prediction(mut self, prediction: MixturePrediction) -> MixtureDensity {
+        self.prediction = prediction;
+        self
+    }
+
+    // a prediction hits when every dimension is within tolerance of the target,
+    // otherwise nothing hits, as exact matches are meaningless for regression
+    pub fn with_tolerance(mut self, tolerance: f64) -> MixtureDensity {
+        assert!(tolerance >= 0., "tolerance should not be negative");
+        self.tolerance = Some(tolerance);
+        self
+    }
+
+    // the same seed gives the same samples of MixturePrediction::Sample
+    pub fn with_seed(mut self, seed: u64) -> MixtureDensity {
+        self.rng = RefCell::new(StdRng::seed_from_u64(seed));
+        self
+    }
+
+    // logits: logits of one sample
+    // return: (weights, means, stddevs) of each component
+    pub fn mixture(&self, logits: &[f64]) -> (Vec<f64>, Vec<Vec<f64>>, Vec<Vec<f64>>) {
+        let (weight_logits, means, log_stddevs) = self.split(logits);
+        (
+            softmax(weight_logits),
+            means,
+            log_stddevs
+                .iter()
+                .map(|row| row.iter().map(|s| s.exp()).collect())
+                .collect(),
+        )
+    }
+
+    // (weight logits, means, log stddevs)
+    fn split<'a>(&self, logits: &'a [f64]) -> (&'a [f64], Vec<Vec<f64>>, Vec<Vec<f64>>) {
+        let (k, dim) = (self.components, self.dim);
+        debug_assert_eq!(
+            logits.len(),
+            k * (2 * dim + 1),
+            "mixture density needs K * (2 * D + 1) outputs"
+        );
+        let (weight_logits, rest) = logits.split_at(k);
+        let (means, log_stddevs) = rest.split_at(k * dim);
+        (
+            weight_logits,
+            means.chunks(dim).map(<[f64]>::to_vec).collect(),
+            log_stddevs.chunks(dim).map(<[f64]>::to_vec).collect(),
+        )
+    }
+
+    // ln(weight(k)) + ln(N(expected | mean(k), stddev(k))) of each component
+    fn log_likelihoods(&self, logits: &[f64], expected: &[f64]) -> Vec<f64> {
+        let (weight_logits, means, log_stddevs) = self.split(logits);
+        log_softmax(weight_logits)
+            .iter()
+            .zip(means.iter().zip(log_stddevs.iter()))
+            .map(|(log_weight, (mean, log_stddev))| {
+                debug_assert_eq!(expected.len(), mean.len());
+                log_weight
+                    + expected
+                        .iter()
+                        .zip(mean.iter().zip(log_stddev.iter()))
+                        .map(|(y, (mu, s))| {
+                            let z = (y - mu) / s.exp();
+                            -0.5 * z * z - s - 0.5 * (2. * PI).ln()
+                        })
+                        .sum::<f64>()
+            })
+            .collect()
+    }
+
+    // logits: logits of one sample
+    // return: prediction of one sample
+    fn predict(&self, logits: &[f64], prediction: &MixturePrediction) -> Vec<f64> {
+        let (weights, means, stddevs) = self.mixture(logits);
+        match prediction {
+            MixturePrediction::Mean => (0..means[0].len())
+                .map(|d| {
+                    weights
+                        .iter()
+                        .zip(means.iter())
+                        .map(|(weight, mean)| weight * mean[d])
+                        .sum()
+                })
+                .collect(),
+            MixturePrediction::Mode => means[argmax(&weights)].clone(),
+            MixturePrediction::Sample => {
+                let rng = &mut *self.rng.borrow_mut();
+                // pick a component by weight
+                let mut u: f64 = rng.gen();
+                let k = weights
+                    .iter()
+                    .position(|weight| {
+                        u -= weight;
+                        u <= 0.
+                    })
+                    .unwrap_or(weights.len() - 1);
+                means[k]
+                    .iter()
+                    .zip(stddevs[k].iter())
+                    .map(|(mean, stddev)| mean + stddev * standard_normal(rng))
+                    .collect()
+            }
+        }
+    }
+}
+
+impl Objective<Linear> for MixtureDensity {
+    // loss = -ln(SUM(weight(k) * N(expected | mean(k), stddev(k))))
+    fn loss(&self, predict: &[Vec<f64>], expected: &[Vec<f64>]) -> Vec<f64> {
+        predict
+            .iter()
+            .zip(expected.iter())
+            .map(|(predict, expected)| {
+                let log_likelihoods = self.log_likelihoods(predict, expected);
+                let max = log_likelihoods
+                    .iter()
+                    .cloned()
+                    .fold(f64::NEG_INFINITY, f64::max);
+                -(log_likelihoods
+                    .iter()
+                    .map(|l| (l - max).exp())
+                    .sum::<f64>()
+                    .ln()
+                    + max)
+            })
+            .collect()
+    }
+
+    // with responsibility r(k) = softmax(log likelihoods)(k) and z = (y - mean) / stddev
+    //     d(weight logit(k)) = weight(k) - r(k)
+    //     d(mean(k))         = -r(k) * z / stddev
+    //     d(log stddev(k))   = r(k) * (1 - z^2)
+    fn delta_without_deriv(&self, predict: &[Vec<f64>], expected: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        predict
+            .iter()
+            .zip(expected.iter())
+            .map(|(predict, expected)| {
+                let responsibilities = softmax(&self.log_likelihoods(predict, expected));
+                let (weight_logits, means, log_stddevs) = self.split(predict);
+                let mut delta: Vec<f64> = softmax(weight_logits)
+                    .iter()
+                    .zip(responsibilities.iter())
+                    .map(|(weight, r)| weight - r)
+                    .collect();
+                let mut mean_deltas = vec![];
+                let mut log_stddev_deltas = vec![];
+                for (r, (mean, log_stddev)) in responsibilities
+                    .iter()
+                    .zip(means.iter().zip(log_stddevs.iter()))
+                {
+                    for (y, (mu, s)) in expected.iter().zip(mean.iter().zip(log_stddev.iter())) {
+                        let stddev = s.exp();
+                        let z = (y - mu) / stddev;
+                        mean_deltas.push(-r * z / stddev);
+                        log_stddev_deltas.push(r * (1. - z * z));
+                    }
+                }
+                delta.extend(mean_deltas);
+                delta.extend(log_stddev_deltas);
+                delta
+            })
+            .collect()
+    }
+
+    // K * (2 * D + 1) outputs for a D dimension label
+    fn is_valid_label(&self, expected: &[f64], num_outputs: usize) -> bool {
+        expected.len() == self.dim && num_outputs == self.components * (2 * self.dim + 1)
+    }
+
+    fn hits(&self, logits: &[Vec<f64>], expected: &[Vec<f64>]) -> Vec<bool> {
+        match self.tolerance {
+            Some(tolerance) => logits
+                .iter()
+                .zip(expected.iter())
+                .map(|(logits, expected)| {
+                    // drawn samples would make the accuracy random
+                    let prediction = match self.prediction {
+                        MixturePrediction::Mode => MixturePrediction::Mode,
+                        MixturePrediction::Mean | MixturePrediction::Sample => {
+                            MixturePrediction::Mean
+                        }
+                    };
+                    self.predict(logits, &prediction)
+                        .iter()
+                        .zip(expected.iter())
+                        .all(|(p, e)| (p - e).abs() <= tolerance)
+                })
+                .collect(),
+            None => vec![false; logits.len()],
+        }
+    }
+
+    fn predict_from_logits(&self, logits: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        logits
+            .iter()
+            .map(|logits| self.predict(logits, &self.prediction))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mixture_density() {
+        // 2 components over 2 dimensions
+        let predict = [vec![0.3, -0.2, 1., 0.5, -1., 2., 0.1, -0.3, 0.2, 0.4]];
+        let expected = [vec![0.8, 1.5]];
+        let mdn = MixtureDensity::new(2, 2);
+
+        let delta = mdn.delta_without_deriv(&predict, &expected);
+        let h = 1e-6;
+        for i in 0..predict[0].len() {
+            let (mut plus, mut minus) = (predict[0].clone(), predict[0].clone());
+            plus[i] += h;
+            minus[i] -= h;
+            let numerical =
+                (mdn.loss(&[plus], &expected)[0] - mdn.loss(&[minus], &expected)[0]) / (2. * h);
+            assert!((delta[0][i] - numerical).abs() < 1e-6);
+        }
+
+        let weights = softmax(&[0.3, -0.2]);
+        let mean = mdn.predict_from_logits(&predict);
+        assert!((mean[0][0] - (weights[0] - weights[1])).abs() < 1e-12);
+        assert!((mean[0][1] - (weights[0] * 0.5 + weights[1] * 2.)).abs() < 1e-12);
+
+        let mdn = MixtureDensity::new(2, 2).with_prediction(MixturePrediction::Mode);
+        assert_eq!(mdn.predict_from_logits(&predict), [[1., 0.5]]);
+        assert_eq!(mdn.hits(&predict, &[vec![1.05, 0.5]]), [false]);
+        let mdn = mdn.with_tolerance(0.1);
+        assert_eq!(mdn.hits(&predict, &[vec![1.05, 0.5]]), [true]);
+
+        let sample = |seed| {
+            MixtureDensity::new(2, 2)
+                .with_prediction(MixturePrediction::Sample)
+                .with_seed(seed)
+                .predict_from_logits(&predict)
+        };
+        assert_eq!(sample(0), sample(0));
+        assert_ne!(sample(0), sample(1));
+
+        // hits of sampling predictions are the mean's, and draw nothing
+        let mdn = MixtureDensity::new(2, 2)
+            .with_prediction(MixturePrediction::Sample)
+            .with_tolerance(0.1)
+            .with_seed(0);
+        assert_eq!(mdn.hits(&predict, &mean), [true]);
+        assert_eq!(mdn.predict_from_logits(&predict), sample(0));
+    }
+}
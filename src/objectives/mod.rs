@@ -1,15 +1,253 @@
 use crate::activators::Activator;
+use crate::functions::{argmax, into_onehot};
+
+use std::cmp::Ordering;
 
 mod binary_cross_entropy;
+mod binary_focal_loss;
+mod contrastive;
+mod cosine_embedding;
 mod cross_entropy;
+mod distillation;
+mod focal_loss;
+mod hinge;
+mod kl_divergence;
 mod mean_square_error;
+mod mixture_density;
+mod multiclass_hinge;
+mod triplet;
 
 pub use binary_cross_entropy::BinaryCrossEntropy;
+pub use binary_focal_loss::BinaryFocalLoss;
+pub use contrastive::Contrastive;
+pub use cosine_embedding::CosineEmbedding;
 pub use cross_entropy::CrossEntropy;
+pub use distillation::Distillation;
+pub use focal_loss::FocalLoss;
+pub use hinge::Hinge;
+pub use kl_divergence::KLDivergence;
 pub use mean_square_error::MeanSquareError;
+pub use mixture_density::{MixtureDensity, MixturePrediction};
+pub use multiclass_hinge::{MulticlassHinge, MulticlassHingeKind};
+pub use triplet::{Triplet, TripletMining};
 
+// objectives get the whole minibatch at once, so besides per-row losses they
+// can pair samples within the batch, eg. metric learning losses
 pub trait Objective<A: Activator> {
+    // predict: minibatch of logits from output layer
+    // expected: minibatch of labels
+    // return: minibatch of losses, whose sum is the loss of the minibatch
     fn loss(&self, predict: &[Vec<f64>], expected: &[Vec<f64>]) -> Vec<f64>;
+    // predict: minibatch of logits from output layer
+    // expected: minibatch of labels
+    // return: minibatch of d(SUM(loss))/d(predict)
     fn delta_without_deriv(&self, predict: &[Vec<f64>], expected: &[Vec<f64>]) -> Vec<Vec<f64>>;
     fn predict_from_logits(&self, logits: &[Vec<f64>]) -> Vec<Vec<f64>>;
+
+    // expected: one label
+    // num_outputs: nodes of the output layer
+    // return: whether the label fits the output layer, checked by fit in debug builds
+    fn is_valid_label(&self, expected: &[f64], num_outputs: usize) -> bool {
+        expected.len() == num_outputs
+    }
+
+    // logits: minibatch of logits from output layer
+    // expected: minibatch of labels
+    // return: minibatch of whether the prediction matches the label
+    fn hits(&self, logits: &[Vec<f64>], expected: &[Vec<f64>]) -> Vec<bool> {
+        self.predict_from_logits(logits)
+            .iter()
+            .zip(expected.iter())
+            .map(|(predict, expected)| predict == expected)
+            .collect()
+    }
+
+    // loss scaled by per-sample weights, with masked outputs left out
+    //
+    // weights: minibatch of sample weights
+    // masks: minibatch of output masks, 0 for a missing target and 1 otherwise
+    // masked samples are evaluated one by one on their unmasked outputs only, so
+    // masks need labels aligned with the outputs and no pairing across samples
+    fn weighted_loss(
+        &self,
+        predict: &[Vec<f64>],
+        expected: &[Vec<f64>],
+        weights: &[f64],
+        masks: Option<&[Vec<f64>]>,
+    ) -> Vec<f64> {
+        let losses = match masks {
+            None => self.loss(predict, expected),
+            Some(masks) => predict
+                .iter()
+                .zip(expected.iter())
+                .zip(masks.iter())
+                .map(|((predict, expected), mask)| {
+                    let (predict, expected) = unmasked(predict, expected, mask);
+                    if predict.is_empty() {
+                        0.
+                    } else {
+                        self.loss(&[predict], &[expected])[0]
+                    }
+                })
+                .collect(),
+        };
+        losses
+            .iter()
+            .zip(weights.iter())
+            .map(|(loss, weight)| loss * weight)
+            .collect()
+    }
+
+    // delta_without_deriv of weighted_loss, masked outputs get no delta
+    fn weighted_delta_without_deriv(
+        &self,
+        predict: &[Vec<f64>],
+        expected: &[Vec<f64>],
+        weights: &[f64],
+        masks: Option<&[Vec<f64>]>,
+    ) -> Vec<Vec<f64>> {
+        let deltas = match masks {
+            None => self.delta_without_deriv(predict, expected),
+            Some(masks) => predict
+                .iter()
+                .zip(expected.iter())
+                .zip(masks.iter())
+                .map(|((predict, expected), mask)| {
+                    let mut delta = vec![0.; predict.len()];
+                    let (predict, expected) = unmasked(predict, expected, mask);
+                    if !predict.is_empty() {
+                        let unmasked_delta = self.delta_without_deriv(&[predict], &[expected]);
+                        (0..delta.len())
+                            .filter(|&i| mask[i] != 0.)
+                            .zip(unmasked_delta[0].iter())
+                            .for_each(|(i, &d)| delta[i] = d);
+                    }
+                    delta
+                })
+                .collect(),
+        };
+        deltas
+            .into_iter()
+            .zip(weights.iter())
+            .map(|(delta, weight)| delta.into_iter().map(|d| d * weight).collect())
+            .collect()
+    }
+}
+
+// (predict, expected) of the outputs not masked out
+fn unmasked(predict: &[f64], expected: &[f64], mask: &[f64]) -> (Vec<f64>, Vec<f64>) {
+    debug_assert_eq!(predict.len(), mask.len(), "one mask per output");
+    debug_assert_eq!(
+        expected.len(),
+        mask.len(),
+        "masked labels should align with outputs"
+    );
+    predict
+        .iter()
+        .zip(expected.iter())
+        .zip(mask.iter())
+        .filter(|(_, &mask)| mask != 0.)
+        .map(|((&predict, &expected), _)| (predict, expected))
+        .unzip()
+}
+
+// class index of a label, which is either onehot (or soft) with one value
+// per class, or sparse with the class index as its only value
+pub(crate) fn target_class(expected: &[f64], classes: usize) -> usize {
+    if expected.len() == 1 && classes > 1 {
+        let idx = expected[0] as usize;
+        debug_assert!(idx < classes, "sparse label must less than classes");
+        idx
+    } else {
+        debug_assert_eq!(expected.len(), classes, "label should be onehot or sparse");
+        argmax(expected)
+    }
+}
+
+// whether a label is onehot (or soft) or sparse
+pub(crate) fn is_onehot_or_sparse(expected: &[f64], classes: usize) -> bool {
+    expected.len() == classes || expected.len() == 1
+}
+
+// onehot (or soft) label with one value per class, expanding sparse labels
+pub(crate) fn dense_target(expected: &[f64], classes: usize) -> Vec<f64> {
+    if expected.len() == 1 && classes > 1 {
+        let mut target = vec![0.; classes];
+        target[target_class(expected, classes)] = 1.;
+        target
+    } else {
+        debug_assert_eq!(expected.len(), classes, "label should be onehot or sparse");
+        expected.to_vec()
+    }
+}
+
+// onehot of the most likely class of each sample, what classifiers predict
+pub(crate) fn onehot_predictions(logits: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    logits
+        .iter()
+        .map(|logits| into_onehot(argmax(logits), logits.len()))
+        .collect()
+}
+
+// a sample is hit when its most likely class is the class of its label,
+// which is either onehot (or soft) or sparse
+pub(crate) fn class_hits<E: AsRef<[f64]>>(logits: &[Vec<f64>], expected: &[E]) -> Vec<bool> {
+    logits
+        .iter()
+        .zip(expected.iter())
+        .map(|(logits, expected)| argmax(logits) == target_class(expected.as_ref(), logits.len()))
+        .collect()
+}
+
+// a sample is hit when its nearest other sample in the minibatch has the same label
+// used by metric learning objectives, whose predictions are embeddings
+pub(crate) fn nearest_neighbour_hits<F>(
+    embeddings: &[Vec<f64>],
+    expected: &[Vec<f64>],
+    distance: F,
+) -> Vec<bool>
+where
+    F: Fn(&[f64], &[f64]) -> f64,
+{
+    embeddings
+        .iter()
+        .enumerate()
+        .map(|(i, embedding)| {
+            embeddings
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .map(|(j, other)| (j, distance(embedding, other)))
+                .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+                .is_some_and(|(j, _)| expected[i] == expected[j])
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weighted_and_masked() {
+        let predict = [vec![0.5, -1., 2.], vec![1., 1., 1.]];
+        let expected = [vec![1., 0., 0.], vec![0., 1., 1.]];
+        let masks = [vec![1., 0., 1.], vec![0., 0., 0.]];
+        let objective = MeanSquareError::new();
+
+        let loss = objective.weighted_loss(&predict, &expected, &[2., 1.], Some(&masks));
+        let unmasked_loss = objective.loss(&[vec![0.5, 2.]], &[vec![1., 0.]]);
+        assert_eq!(loss, [2. * unmasked_loss[0], 0.]);
+
+        let delta =
+            objective.weighted_delta_without_deriv(&predict, &expected, &[2., 1.], Some(&masks));
+        let unmasked_delta = objective.delta_without_deriv(&[vec![0.5, 2.]], &[vec![1., 0.]]);
+        assert_eq!(
+            delta,
+            [
+                vec![2. * unmasked_delta[0][0], 0., 2. * unmasked_delta[0][1]],
+                vec![0., 0., 0.]
+            ]
+        );
+    }
 }
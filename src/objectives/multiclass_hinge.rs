@@ -0,0 +1,134 @@
+use super::{class_hits, is_onehot_or_sparse, onehot_predictions, target_class, Objective};
+use crate::activators::Linear;
+
+use std::cmp::Ordering;
+
+pub enum MulticlassHingeKind {
+    // loss = max(0, 1 + max(predict(j), j != y) - predict(y))
+    // http://jmlr.csail.mit.edu/papers/volume2/crammer01a/crammer01a.pdf
+    CrammerSinger,
+    // loss = SUM(max(0, 1 + predict(j) - predict(y)), j != y)
+    // https://www.elen.ucl.ac.be/Proceedings/esann/esannpdf/es1999-461.pdf
+    WestonWatkins,
+}
+
+// multi-class hinge loss of a linear svm, labels can be onehot or sparse
+pub struct MulticlassHinge {
+    kind: MulticlassHingeKind,
+    squared: bool,
+}
+
+impl MulticlassHinge {
+    pub fn new(kind: MulticlassHingeKind) -> MulticlassHinge {
+        MulticlassHinge {
+            kind,
+            squared: false,
+        }
+    }
+
+    // square each margin violation
+    pub fn with_squared(mut self) -> MulticlassHinge {
+        self.squared = true;
+        self
+    }
+
+    // margin violations as (class j, 1 + predict(j) - predict(y)), only positive ones
+    fn violations(&self, predict: &[f64], expected: &[f64]) -> Vec<(usize, f64)> {
+        let y = target_class(expected, predict.len());
+        let margins = predict
+            .iter()
+            .enumerate()
+            .filter(|&(j, _)| j != y)
+            .map(|(j, predict_j)| (j, 1. + predict_j - predict[y]));
+        let violations: Vec<(usize, f64)> = match self.kind {
+            MulticlassHingeKind::CrammerSinger => margins
+                .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+                .into_iter()
+                .collect(),
+            MulticlassHingeKind::WestonWatkins => margins.collect(),
+        };
+        violations
+            .into_iter()
+            .filter(|&(_, margin)| margin > 0.)
+            .collect()
+    }
+}
+
+impl Objective<Linear> for MulticlassHinge {
+    fn loss(&self, predict: &[Vec<f64>], expected: &[Vec<f64>]) -> Vec<f64> {
+        predict
+            .iter()
+            .zip(expected.iter())
+            .map(|(predict, expected)| {
+                self.violations(predict, expected)
+                    .iter()
+                    .map(|&(_, margin)| {
+                        if self.squared {
+                            margin * margin
+                        } else {
+                            margin
+                        }
+                    })
+                    .sum()
+            })
+            .collect()
+    }
+
+    // each violated margin pushes predict(j) down and predict(y) up
+    fn delta_without_deriv(&self, predict: &[Vec<f64>], expected: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        predict
+            .iter()
+            .zip(expected.iter())
+            .map(|(predict, expected)| {
+                let y = target_class(expected, predict.len());
+                let mut delta = vec![0.; predict.len()];
+                self.violations(predict, expected)
+                    .iter()
+                    .for_each(|&(j, margin)| {
+                        let deriv = if self.squared { 2. * margin } else { 1. };
+                        delta[j] += deriv;
+                        delta[y] -= deriv;
+                    });
+                delta
+            })
+            .collect()
+    }
+
+    fn is_valid_label(&self, expected: &[f64], num_outputs: usize) -> bool {
+        is_onehot_or_sparse(expected, num_outputs)
+    }
+
+    fn predict_from_logits(&self, logits: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        onehot_predictions(logits)
+    }
+
+    fn hits(&self, logits: &[Vec<f64>], expected: &[Vec<f64>]) -> Vec<bool> {
+        class_hits(logits, expected)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multiclass_hinge() {
+        let predict = [vec![1., 1.5, 0.], vec![3., 0., 1.]];
+        let expected = [vec![0.], vec![1., 0., 0.]];
+
+        let ww = MulticlassHinge::new(MulticlassHingeKind::WestonWatkins);
+        assert_eq!(ww.loss(&predict, &expected), [1.5, 0.]);
+        assert_eq!(
+            ww.delta_without_deriv(&predict, &expected),
+            [[-1., 1., 0.], [0., 0., 0.]]
+        );
+
+        let cs = MulticlassHinge::new(MulticlassHingeKind::CrammerSinger).with_squared();
+        assert_eq!(cs.loss(&predict, &expected), [2.25, 0.]);
+        assert_eq!(
+            cs.delta_without_deriv(&predict, &expected),
+            [[-3., 3., 0.], [0., 0., 0.]]
+        );
+        assert_eq!(cs.hits(&predict, &expected), [false, true]);
+    }
+}
@@ -0,0 +1,215 @@
+use super::{nearest_neighbour_hits, Objective};
+use crate::activators::Linear;
+use crate::functions::euclidean_distance;
+
+use std::cmp::Ordering;
+
+// how triplets are mined from the minibatch for each anchor
+pub enum TripletMining {
+    // mean over every valid (positive, negative) pair
+    BatchAll,
+    // only the farthest positive and the closest negative
+    // https://arxiv.org/abs/1703.07737
+    BatchHard,
+}
+
+// https://arxiv.org/abs/1503.03832
+//
+// predictions are embeddings and labels are class ids, eg. vec![3.]
+// every sample is an anchor, and its triplets are mined from the minibatch
+//     loss(anchor) = max(0, margin + d(anchor, positive) - d(anchor, negative))
+// anchors without a positive or a negative in the minibatch have no loss
+// with sample weights an anchor's triplets are weighted by the anchor's weight,
+// masks are not supported
+pub struct Triplet {
+    margin: f64,
+    mining: TripletMining,
+}
+
+impl Triplet {
+    pub fn new(margin: f64, mining: TripletMining) -> Triplet {
+        Triplet { margin, mining }
+    }
+
+    // (positive, negative, weight) of the triplets mined for each anchor
+    fn triplets(
+        &self,
+        predict: &[Vec<f64>],
+        expected: &[Vec<f64>],
+    ) -> Vec<Vec<(usize, usize, f64)>> {
+        let n = predict.len();
+        (0..n)
+            .map(|a| {
+                let distances: Vec<f64> = (0..n)
+                    .map(|i| euclidean_distance(&predict[a], &predict[i]))
+                    .collect();
+                let positives: Vec<usize> = (0..n)
+                    .filter(|&i| i != a && expected[i] == expected[a])
+                    .collect();
+                let negatives: Vec<usize> =
+                    (0..n).filter(|&i| expected[i] != expected[a]).collect();
+                let by_distance = |&i: &usize, &j: &usize| {
+                    distances[i]
+                        .partial_cmp(&distances[j])
+                        .unwrap_or(Ordering::Equal)
+                };
+                match self.mining {
+                    TripletMining::BatchAll => {
+                        let weight = 1. / (positives.len() * negatives.len()).max(1) as f64;
+                        positives
+                            .iter()
+                            .flat_map(|&p| negatives.iter().map(move |&n| (p, n, weight)))
+                            .collect()
+                    }
+                    TripletMining::BatchHard => {
+                        let positive = positives.iter().max_by(|i, j| by_distance(i, j));
+                        let negative = negatives.iter().min_by(|i, j| by_distance(i, j));
+                        positive
+                            .zip(negative)
+                            .map(|(&p, &n)| (p, n, 1.))
+                            .into_iter()
+                            .collect()
+                    }
+                }
+            })
+            .collect()
+    }
+
+    fn triplet_loss(&self, predict: &[Vec<f64>], a: usize, p: usize, n: usize) -> f64 {
+        self.margin + euclidean_distance(&predict[a], &predict[p])
+            - euclidean_distance(&predict[a], &predict[n])
+    }
+
+    fn losses(&self, predict: &[Vec<f64>], expected: &[Vec<f64>], weights: &[f64]) -> Vec<f64> {
+        self.triplets(predict, expected)
+            .iter()
+            .enumerate()
+            .map(|(a, triplets)| {
+                triplets
+                    .iter()
+                    .map(|&(p, n, weight)| weight * self.triplet_loss(predict, a, p, n).max(0.))
+                    .sum::<f64>()
+                    * weights[a]
+            })
+            .collect()
+    }
+
+    // d(d(a, b))/d(a) = (a - b) / d(a, b), and the opposite for b
+    fn deltas(
+        &self,
+        predict: &[Vec<f64>],
+        expected: &[Vec<f64>],
+        weights: &[f64],
+    ) -> Vec<Vec<f64>> {
+        let mut deltas = vec![vec![0.; predict.first().map_or(0, Vec::len)]; predict.len()];
+        let mut add = |a: usize, b: usize, scale: f64| {
+            let distance = euclidean_distance(&predict[a], &predict[b]);
+            if distance < 1e-12 {
+                return;
+            }
+            for k in 0..predict[a].len() {
+                let grad = scale * (predict[a][k] - predict[b][k]) / distance;
+                deltas[a][k] += grad;
+                deltas[b][k] -= grad;
+            }
+        };
+        for (a, triplets) in self.triplets(predict, expected).iter().enumerate() {
+            for &(p, n, weight) in triplets {
+                if self.triplet_loss(predict, a, p, n) > 0. {
+                    add(a, p, weights[a] * weight);
+                    add(a, n, -weights[a] * weight);
+                }
+            }
+        }
+        deltas
+    }
+}
+
+impl Objective<Linear> for Triplet {
+    fn loss(&self, predict: &[Vec<f64>], expected: &[Vec<f64>]) -> Vec<f64> {
+        self.losses(predict, expected, &vec![1.; predict.len()])
+    }
+
+    fn delta_without_deriv(&self, predict: &[Vec<f64>], expected: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        self.deltas(predict, expected, &vec![1.; predict.len()])
+    }
+
+    fn weighted_loss(
+        &self,
+        predict: &[Vec<f64>],
+        expected: &[Vec<f64>],
+        weights: &[f64],
+        masks: Option<&[Vec<f64>]>,
+    ) -> Vec<f64> {
+        assert!(masks.is_none(), "triplet loss doesn't support masks");
+        self.losses(predict, expected, weights)
+    }
+
+    fn weighted_delta_without_deriv(
+        &self,
+        predict: &[Vec<f64>],
+        expected: &[Vec<f64>],
+        weights: &[f64],
+        masks: Option<&[Vec<f64>]>,
+    ) -> Vec<Vec<f64>> {
+        assert!(masks.is_none(), "triplet loss doesn't support masks");
+        self.deltas(predict, expected, weights)
+    }
+
+    // labels are class ids
+    fn is_valid_label(&self, expected: &[f64], _: usize) -> bool {
+        expected.len() == 1
+    }
+
+    fn predict_from_logits(&self, logits: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        logits.to_owned()
+    }
+
+    fn hits(&self, logits: &[Vec<f64>], expected: &[Vec<f64>]) -> Vec<bool> {
+        nearest_neighbour_hits(logits, expected, euclidean_distance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_triplet() {
+        let predict = [
+            vec![0., 1.],
+            vec![0.5, 0.8],
+            vec![0.1, 0.6],
+            vec![0.9, 0.],
+            vec![0.35, 0.4],
+        ];
+        let expected = [vec![0.], vec![0.], vec![1.], vec![1.], vec![2.]];
+        // uneven sample weights, an anchor weights its triplets
+        let weights = [1., 2., 0.5, 1.5, 3.];
+
+        for mining in [TripletMining::BatchAll, TripletMining::BatchHard] {
+            let triplet = Triplet::new(1., mining);
+            let delta = triplet.weighted_delta_without_deriv(&predict, &expected, &weights, None);
+            let h = 1e-6;
+            for i in 0..predict.len() {
+                for k in 0..2 {
+                    let (mut plus, mut minus) = (predict.to_vec(), predict.to_vec());
+                    plus[i][k] += h;
+                    minus[i][k] -= h;
+                    let numerical = (triplet
+                        .weighted_loss(&plus, &expected, &weights, None)
+                        .iter()
+                        .sum::<f64>()
+                        - triplet
+                            .weighted_loss(&minus, &expected, &weights, None)
+                            .iter()
+                            .sum::<f64>())
+                        / (2. * h);
+                    assert!((delta[i][k] - numerical).abs() < 1e-6);
+                }
+            }
+            // anchor 4 has no positive in the minibatch
+            assert_eq!(triplet.loss(&predict, &expected)[4], 0.);
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use super::{grads, layer_state, params, LayerState, Optimizer};
+
+pub struct Adadelta {
+    pub learning_rate: f64,
+    rho: f64,
+    eps: f64,
+    squares: Vec<Option<LayerState>>,
+    delta_squares: Vec<Option<LayerState>>,
+}
+
+impl Adadelta {
+    // the paper has no learning rate, which is the same as learning_rate = 1
+    pub fn new(learning_rate: f64) -> Adadelta {
+        Adadelta {
+            learning_rate,
+            rho: 0.9,
+            eps: 1e-6,
+            squares: vec![],
+            delta_squares: vec![],
+        }
+    }
+
+    // decay rate of the running averages
+    pub fn with_rho(mut self, rho: f64) -> Adadelta {
+        self.rho = rho;
+        self
+    }
+
+    pub fn with_eps(mut self, eps: f64) -> Adadelta {
+        self.eps = eps;
+        self
+    }
+}
+
+// https://arxiv.org/abs/1212.5701
+impl Optimizer for Adadelta {
+    fn learning_rate(&self) -> f64 {
+        self.learning_rate
+    }
+
+    fn set_learning_rate(&mut self, learning_rate: f64) {
+        self.learning_rate = learning_rate;
+    }
+
+    fn optimize(
+        &mut self,
+        idx: usize,
+        weights: &mut [Vec<f64>],
+        bias: &mut [f64],
+        gradients: &mut [Vec<f64>],
+        bias_gradients: &mut [f64],
+    ) {
+        let squares = layer_state(&mut self.squares, idx, || {
+            LayerState::new(gradients, bias_gradients, 0.)
+        });
+        let delta_squares = layer_state(&mut self.delta_squares, idx, || {
+            LayerState::new(gradients, bias_gradients, 0.)
+        });
+
+        let (learning_rate, rho, eps) = (self.learning_rate, self.rho, self.eps);
+        params(weights, bias)
+            .zip(grads(gradients, bias_gradients))
+            .zip(squares.iter_mut().zip(delta_squares.iter_mut()))
+            .for_each(|((param, gradient), (square, delta_square))| {
+                // square(t) = rho * square(t-1) + (1 - rho) * gradient(t)^2
+                *square = rho * *square + (1. - rho) * gradient.powf(2.);
+                // delta(t) = (delta_square(t-1) + eps).sqrt() / (square(t) + eps).sqrt() * gradient(t)
+                let delta = (*delta_square + eps).sqrt() / (*square + eps).sqrt() * gradient;
+                // delta_square(t) = rho * delta_square(t-1) + (1 - rho) * delta(t)^2
+                *delta_square = rho * *delta_square + (1. - rho) * delta.powf(2.);
+                *param -= learning_rate * delta;
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimizers::tests::{assert_close, optimize_steps};
+
+    #[test]
+    fn test_adadelta() {
+        assert_close(
+            &optimize_steps(&mut Adadelta::new(1.), 6),
+            &[
+                0.48890613324367865,
+                -0.3051993078426119,
+                0.09573692018987288,
+            ],
+        );
+    }
+}
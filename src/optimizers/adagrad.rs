@@ -0,0 +1,80 @@
+use super::{grads, layer_state, params, LayerState, Optimizer};
+
+pub struct Adagrad {
+    pub learning_rate: f64,
+    initial_accumulator: f64,
+    eps: f64,
+    squares: Vec<Option<LayerState>>,
+}
+
+impl Adagrad {
+    pub fn new(learning_rate: f64) -> Adagrad {
+        Adagrad {
+            learning_rate,
+            initial_accumulator: 0.,
+            eps: 1e-10,
+            squares: vec![],
+        }
+    }
+
+    // starting value of the sum of squared gradients
+    pub fn with_initial_accumulator(mut self, initial_accumulator: f64) -> Adagrad {
+        self.initial_accumulator = initial_accumulator;
+        self
+    }
+
+    pub fn with_eps(mut self, eps: f64) -> Adagrad {
+        self.eps = eps;
+        self
+    }
+}
+
+// https://jmlr.org/papers/v12/duchi11a.html
+impl Optimizer for Adagrad {
+    fn learning_rate(&self) -> f64 {
+        self.learning_rate
+    }
+
+    fn set_learning_rate(&mut self, learning_rate: f64) {
+        self.learning_rate = learning_rate;
+    }
+
+    fn optimize(
+        &mut self,
+        idx: usize,
+        weights: &mut [Vec<f64>],
+        bias: &mut [f64],
+        gradients: &mut [Vec<f64>],
+        bias_gradients: &mut [f64],
+    ) {
+        let initial_accumulator = self.initial_accumulator;
+        let squares = layer_state(&mut self.squares, idx, || {
+            LayerState::new(gradients, bias_gradients, initial_accumulator)
+        });
+
+        let (learning_rate, eps) = (self.learning_rate, self.eps);
+        params(weights, bias)
+            .zip(grads(gradients, bias_gradients))
+            .zip(squares.iter_mut())
+            .for_each(|((param, gradient), square)| {
+                // square(t) = square(t-1) + gradient(t)^2
+                *square += gradient.powf(2.);
+                // param(t) = param(t-1) - learning_rate * gradient(t) / (square(t).sqrt() + eps)
+                *param -= learning_rate * gradient / (square.sqrt() + eps);
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimizers::tests::{assert_close, optimize_steps};
+
+    #[test]
+    fn test_adagrad() {
+        assert_close(
+            &optimize_steps(&mut Adagrad::new(0.1), 6),
+            &[0.275324477110031, -0.3491643478963435, -0.02334081961947685],
+        );
+    }
+}
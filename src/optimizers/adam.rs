@@ -1,4 +1,4 @@
-use super::Optimizer;
+use super::{layer_state, LayerState, Optimizer};
 use crate::functions::{for_each, transform};
 
 pub struct Adam {
@@ -6,11 +6,9 @@ pub struct Adam {
     beta1: f64,
     beta2: f64,
     eps: f64,
-    count: u64,
-    means: Vec<Vec<Vec<f64>>>,
-    bias_means: Vec<Vec<f64>>,
-    virances: Vec<Vec<Vec<f64>>>,
-    bias_virances: Vec<Vec<f64>>,
+    counts: Vec<Option<u64>>,
+    means: Vec<Option<LayerState>>,
+    virances: Vec<Option<LayerState>>,
 }
 
 impl Adam {
@@ -20,29 +18,22 @@ impl Adam {
             beta1: 0.9,
             beta2: 0.999,
             eps: 0.00000001,
-            count: 0,
+            counts: vec![],
             means: vec![],
-            bias_means: vec![],
             virances: vec![],
-            bias_virances: vec![],
         }
     }
 
-    // init mean and virance default 0
-    fn init_layer_mean_and_virance(&mut self, gradients: &[Vec<f64>], bias_gradients: &[f64]) {
-        let mean: Vec<Vec<f64>> = gradients
-            .iter()
-            .map(|row| row.iter().map(|_| 0.).collect())
-            .collect();
-        let bias_mean: Vec<f64> = bias_gradients.iter().map(|_| 0.).collect();
-
-        let virance = mean.clone();
-        let bias_virance = bias_mean.clone();
-
-        self.means.push(mean);
-        self.bias_means.push(bias_mean);
-        self.virances.push(virance);
-        self.bias_virances.push(bias_virance);
+    // decay rates of the running averages of gradients and squared gradients
+    pub fn with_betas(mut self, beta1: f64, beta2: f64) -> Adam {
+        self.beta1 = beta1;
+        self.beta2 = beta2;
+        self
+    }
+
+    pub fn with_eps(mut self, eps: f64) -> Adam {
+        self.eps = eps;
+        self
     }
 }
 
@@ -50,6 +41,14 @@ impl Adam {
 // https://blog.csdn.net/yzy_1996/article/details/84618536
 // https://zh.d2l.ai/chapter_optimization/adam.html
 impl Optimizer for Adam {
+    fn learning_rate(&self) -> f64 {
+        self.learning_rate
+    }
+
+    fn set_learning_rate(&mut self, learning_rate: f64) {
+        self.learning_rate = learning_rate;
+    }
+
     fn optimize(
         &mut self,
         idx: usize,
@@ -58,46 +57,48 @@ impl Optimizer for Adam {
         gradients: &mut [Vec<f64>],
         bias_gradients: &mut [f64],
     ) {
-        // increased after every all layers updated
-        if idx == 0 {
-            self.count += 1;
-        }
+        // increased after every update of this layer
+        let count = layer_state(&mut self.counts, idx, || 0);
+        *count += 1;
+        let param = *count as f64;
 
-        if self.means.len() <= idx {
-            self.init_layer_mean_and_virance(gradients, bias_gradients);
-        }
+        // init mean and virance default 0
+        let mean = layer_state(&mut self.means, idx, || {
+            LayerState::new(gradients, bias_gradients, 0.)
+        });
+        let virance = layer_state(&mut self.virances, idx, || {
+            LayerState::new(gradients, bias_gradients, 0.)
+        });
 
         let beta1 = self.beta1;
         let beta2 = self.beta2;
-        let param = self.count as f64;
 
         // step1. mean(t) = beta1 * mean(t-1) + (1 - beta1) * gradient(t)
         transform(
-            &mut self.means[idx],
+            &mut mean.weights,
             gradients,
-            &mut self.bias_means[idx],
+            &mut mean.bias,
             bias_gradients,
             |mean, gradient| *mean = beta1 * *mean + (1. - beta1) * gradient,
         );
 
         // step2. viranece(t) = beta2 * virance(t-1) + (1 - beta2) * gradient(t)^2
         transform(
-            &mut self.virances[idx],
+            &mut virance.weights,
             gradients,
-            &mut self.bias_virances[idx],
+            &mut virance.bias,
             bias_gradients,
             |mean, gradient| *mean = beta2 * *mean + (1. - beta2) * gradient.powf(2.),
         );
 
         // step3. mean_bias_corr(t) = mean(t) / (1 - beta1^param(t))
-        let (mut corr_mean, mut bias_corr_mean) =
-            for_each(&self.means[idx], &self.bias_means[idx], |mean| {
-                mean / (1. - beta1.powf(param))
-            });
+        let (mut corr_mean, mut bias_corr_mean) = for_each(&mean.weights, &mean.bias, |mean| {
+            mean / (1. - beta1.powf(param))
+        });
 
         // step4. virance_bias_corr(t) = virance(t) / (1 - beta2^param(t))
         let (corr_virance, bias_corr_virance) =
-            for_each(&self.virances[idx], &self.bias_virances[idx], |virance| {
+            for_each(&virance.weights, &virance.bias, |virance| {
                 virance / (1. - beta2.powf(param))
             });
 
@@ -107,7 +108,7 @@ impl Optimizer for Adam {
             &corr_virance,
             &mut bias_corr_mean,
             &bias_corr_virance,
-            |mean, virance| *mean = *mean / (virance.sqrt() + self.eps),
+            |mean, virance| *mean /= virance.sqrt() + self.eps,
         );
 
         // step6. update weights and bias
@@ -121,3 +122,17 @@ impl Optimizer for Adam {
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimizers::tests::{assert_close, optimize_steps};
+
+    #[test]
+    fn test_adam() {
+        assert_close(
+            &optimize_steps(&mut Adam::new(0.1).with_betas(0.8, 0.99), 6),
+            &[0.1196818891314479, -0.3814699327402888, -0.2150226634691678],
+        );
+    }
+}
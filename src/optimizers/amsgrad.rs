@@ -0,0 +1,110 @@
+use super::{grads, layer_state, params, LayerState, Optimizer};
+
+pub struct AMSGrad {
+    pub learning_rate: f64,
+    beta1: f64,
+    beta2: f64,
+    eps: f64,
+    counts: Vec<Option<u64>>,
+    means: Vec<Option<LayerState>>,
+    virances: Vec<Option<LayerState>>,
+    max_virances: Vec<Option<LayerState>>,
+}
+
+impl AMSGrad {
+    pub fn new(learning_rate: f64) -> AMSGrad {
+        AMSGrad {
+            learning_rate,
+            beta1: 0.9,
+            beta2: 0.999,
+            eps: 1e-8,
+            counts: vec![],
+            means: vec![],
+            virances: vec![],
+            max_virances: vec![],
+        }
+    }
+
+    // decay rates of the running averages of gradients and squared gradients
+    pub fn with_betas(mut self, beta1: f64, beta2: f64) -> AMSGrad {
+        self.beta1 = beta1;
+        self.beta2 = beta2;
+        self
+    }
+
+    pub fn with_eps(mut self, eps: f64) -> AMSGrad {
+        self.eps = eps;
+        self
+    }
+}
+
+// https://openreview.net/forum?id=ryQu7f-RZ
+//
+// Adam with the maximum of all virances so far, which never increases the step size
+impl Optimizer for AMSGrad {
+    fn learning_rate(&self) -> f64 {
+        self.learning_rate
+    }
+
+    fn set_learning_rate(&mut self, learning_rate: f64) {
+        self.learning_rate = learning_rate;
+    }
+
+    fn optimize(
+        &mut self,
+        idx: usize,
+        weights: &mut [Vec<f64>],
+        bias: &mut [f64],
+        gradients: &mut [Vec<f64>],
+        bias_gradients: &mut [f64],
+    ) {
+        // increased after every update of this layer
+        let count = layer_state(&mut self.counts, idx, || 0);
+        *count += 1;
+        let param = *count as f64;
+
+        let means = layer_state(&mut self.means, idx, || {
+            LayerState::new(gradients, bias_gradients, 0.)
+        });
+        let virances = layer_state(&mut self.virances, idx, || {
+            LayerState::new(gradients, bias_gradients, 0.)
+        });
+        let max_virances = layer_state(&mut self.max_virances, idx, || {
+            LayerState::new(gradients, bias_gradients, 0.)
+        });
+
+        let (learning_rate, beta1, beta2, eps) =
+            (self.learning_rate, self.beta1, self.beta2, self.eps);
+        params(weights, bias)
+            .zip(grads(gradients, bias_gradients))
+            .zip(means.iter_mut().zip(virances.iter_mut()))
+            .zip(max_virances.iter_mut())
+            .for_each(|(((param_t, gradient), (mean, virance)), max_virance)| {
+                *mean = beta1 * *mean + (1. - beta1) * gradient;
+                *virance = beta2 * *virance + (1. - beta2) * gradient.powf(2.);
+                // max_virance(t) = max(max_virance(t-1), virance(t))
+                *max_virance = max_virance.max(*virance);
+                let corr_mean = *mean / (1. - beta1.powf(param));
+                let corr_virance = *max_virance / (1. - beta2.powf(param));
+                *param_t -= learning_rate * corr_mean / (corr_virance.sqrt() + eps);
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimizers::tests::{assert_close, optimize_steps};
+
+    #[test]
+    fn test_amsgrad() {
+        assert_close(
+            &optimize_steps(&mut AMSGrad::new(0.1), 6),
+            &[
+                0.11957162029587677,
+                -0.3670133566096566,
+                -0.23670064939035815,
+            ],
+        );
+    }
+}
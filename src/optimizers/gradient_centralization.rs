@@ -0,0 +1,75 @@
+use super::Optimizer;
+
+pub struct GradientCentralization<O: Optimizer> {
+    optimizer: O,
+}
+
+impl<O: Optimizer> GradientCentralization<O> {
+    pub fn new(optimizer: O) -> GradientCentralization<O> {
+        GradientCentralization { optimizer }
+    }
+
+    // the wrapped optimizer
+    pub fn inner(&self) -> &O {
+        &self.optimizer
+    }
+
+    pub fn inner_mut(&mut self) -> &mut O {
+        &mut self.optimizer
+    }
+}
+
+// https://arxiv.org/abs/2004.01461
+//
+// centralizes each node's incoming weight gradients to zero mean before the
+// wrapped optimizer, bias gradients are left as they are
+impl<O: Optimizer> Optimizer for GradientCentralization<O> {
+    fn learning_rate(&self) -> f64 {
+        self.optimizer.learning_rate()
+    }
+
+    fn set_learning_rate(&mut self, learning_rate: f64) {
+        self.optimizer.set_learning_rate(learning_rate);
+    }
+
+    fn optimize(
+        &mut self,
+        idx: usize,
+        weights: &mut [Vec<f64>],
+        bias: &mut [f64],
+        gradients: &mut [Vec<f64>],
+        bias_gradients: &mut [f64],
+    ) {
+        gradients
+            .iter_mut()
+            .filter(|row| row.len() > 1)
+            .for_each(|row| {
+                let mean = row.iter().sum::<f64>() / row.len() as f64;
+                row.iter_mut().for_each(|g| *g -= mean);
+            });
+        self.optimizer
+            .optimize(idx, weights, bias, gradients, bias_gradients);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimizers::SGD;
+
+    #[test]
+    fn test_gradient_centralization() {
+        let mut weights = vec![vec![1., 1.], vec![1., 1.]];
+        let mut bias = vec![0., 0.];
+        let mut gc = GradientCentralization::new(SGD::new(1.));
+        gc.optimize(
+            0,
+            &mut weights,
+            &mut bias,
+            &mut [vec![1., 3.], vec![-2., 2.]],
+            &mut [1., 2.],
+        );
+        assert_eq!(weights, [[2., 0.], [3., -1.]]);
+        assert_eq!(bias, [-1., -2.]);
+    }
+}
@@ -0,0 +1,120 @@
+use super::{layer_state, Optimizer};
+use crate::functions::standard_normal;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+pub struct GradientNoise<O: Optimizer> {
+    optimizer: O,
+    eta: f64,
+    gamma: f64,
+    counts: Vec<Option<u64>>,
+    rng: StdRng,
+}
+
+impl<O: Optimizer> GradientNoise<O> {
+    // eta: variance of the noise at the first step, eg. 0.01, 0.3 or 1
+    pub fn new(optimizer: O, eta: f64) -> GradientNoise<O> {
+        GradientNoise {
+            optimizer,
+            eta,
+            gamma: 0.55,
+            counts: vec![],
+            rng: StdRng::from_entropy(),
+        }
+    }
+
+    // decay rate of the noise's variance over steps
+    pub fn with_gamma(mut self, gamma: f64) -> GradientNoise<O> {
+        self.gamma = gamma;
+        self
+    }
+
+    // the same seed gives the same noise, for reproducible runs
+    pub fn with_seed(mut self, seed: u64) -> GradientNoise<O> {
+        self.rng = StdRng::seed_from_u64(seed);
+        self
+    }
+
+    // the wrapped optimizer
+    pub fn inner(&self) -> &O {
+        &self.optimizer
+    }
+
+    pub fn inner_mut(&mut self) -> &mut O {
+        &mut self.optimizer
+    }
+}
+
+// https://arxiv.org/abs/1511.06807
+//
+// adds gaussian noise to the gradients before the wrapped optimizer
+//     gradient(t) = gradient(t) + N(0, eta / (1 + t)^gamma)
+impl<O: Optimizer> Optimizer for GradientNoise<O> {
+    fn learning_rate(&self) -> f64 {
+        self.optimizer.learning_rate()
+    }
+
+    fn set_learning_rate(&mut self, learning_rate: f64) {
+        self.optimizer.set_learning_rate(learning_rate);
+    }
+
+    fn optimize(
+        &mut self,
+        idx: usize,
+        weights: &mut [Vec<f64>],
+        bias: &mut [f64],
+        gradients: &mut [Vec<f64>],
+        bias_gradients: &mut [f64],
+    ) {
+        // t counts from 0 for each layer
+        let count = layer_state(&mut self.counts, idx, || 0);
+        let stddev = (self.eta / (1. + *count as f64).powf(self.gamma)).sqrt();
+        *count += 1;
+
+        let rng = &mut self.rng;
+        gradients
+            .iter_mut()
+            .flatten()
+            .chain(bias_gradients.iter_mut())
+            .for_each(|g| *g += stddev * standard_normal(rng));
+        self.optimizer
+            .optimize(idx, weights, bias, gradients, bias_gradients);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimizers::SGD;
+
+    // variance of the noise added to zero gradients over the first steps
+    fn variances(seed: u64) -> Vec<f64> {
+        let mut noise = GradientNoise::new(SGD::new(1.), 0.5).with_seed(seed);
+        (0..10)
+            .map(|_| {
+                let mut weights = vec![vec![0.; 10000]];
+                noise.optimize(
+                    0,
+                    &mut weights,
+                    &mut [0.],
+                    &mut [vec![0.; 10000]],
+                    &mut [0.],
+                );
+                weights[0].iter().map(|w| w * w).sum::<f64>() / 10000.
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_gradient_noise() {
+        let seeded = variances(0);
+        assert_eq!(seeded, variances(0));
+        assert_ne!(seeded, variances(1));
+        // eta / (1 + t)^gamma
+        for (t, variance) in seeded.iter().enumerate() {
+            let expected = 0.5 / (1. + t as f64).powf(0.55);
+            assert!((variance / expected - 1.).abs() < 0.05);
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use super::ClosureOptimizer;
+
+use std::collections::VecDeque;
+
+pub struct LBFGS {
+    pub learning_rate: f64,
+    history_size: usize,
+    tolerance_grad: f64,
+    max_evals: usize,
+    // (s, y, 1 / y.s) of the latest steps, s = x(t+1) - x(t), y = g(t+1) - g(t)
+    history: VecDeque<(Vec<f64>, Vec<f64>, f64)>,
+    // (parameters, loss, gradients) of the last evaluation, reused by the next step
+    last: Option<(Vec<f64>, f64, Vec<f64>)>,
+}
+
+impl LBFGS {
+    pub fn new(learning_rate: f64) -> LBFGS {
+        LBFGS {
+            learning_rate,
+            history_size: 10,
+            tolerance_grad: 1e-7,
+            max_evals: 20,
+            history: VecDeque::new(),
+            last: None,
+        }
+    }
+
+    // number of steps to approximate the inverse hessian with
+    pub fn with_history_size(mut self, history_size: usize) -> LBFGS {
+        assert!(history_size > 0, "history size should be positive");
+        self.history_size = history_size;
+        self
+    }
+
+    // stop once the largest gradient is within tolerance_grad
+    pub fn with_tolerance_grad(mut self, tolerance_grad: f64) -> LBFGS {
+        self.tolerance_grad = tolerance_grad;
+        self
+    }
+
+    // max loss evaluations of the line search in one step
+    pub fn with_max_evals(mut self, max_evals: usize) -> LBFGS {
+        assert!(max_evals > 0, "max evals should be positive");
+        self.max_evals = max_evals;
+        self
+    }
+
+    // two-loop recursion, direction = -H * gradients
+    fn direction(&self, gradients: &[f64]) -> Vec<f64> {
+        let mut q = gradients.to_vec();
+        let mut alphas = vec![];
+        for (s, y, rho) in self.history.iter().rev() {
+            let alpha = rho * dot(s, &q);
+            q.iter_mut()
+                .zip(y.iter())
+                .for_each(|(q, y)| *q -= alpha * y);
+            alphas.push(alpha);
+        }
+        // H0 = s.y / y.y of the latest step
+        if let Some((s, y, _)) = self.history.back() {
+            let gamma = dot(s, y) / dot(y, y);
+            q.iter_mut().for_each(|q| *q *= gamma);
+        }
+        for ((s, y, rho), alpha) in self.history.iter().zip(alphas.iter().rev()) {
+            let beta = rho * dot(y, &q);
+            q.iter_mut()
+                .zip(s.iter())
+                .for_each(|(q, s)| *q += (alpha - beta) * s);
+        }
+        q.iter().map(|q| -q).collect()
+    }
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b.iter()).map(|(a, b)| a * b).sum()
+}
+
+// https://en.wikipedia.org/wiki/Limited-memory_BFGS
+// https://pytorch.org/docs/stable/generated/torch.optim.LBFGS.html
+//
+// one iteration per step, with a backtracking line search until the Armijo
+// condition loss(x + t * d) <= loss(x) + c1 * t * g.d holds
+impl ClosureOptimizer for LBFGS {
+    fn step<F>(&mut self, parameters: &mut Vec<f64>, mut closure: F) -> f64
+    where
+        F: FnMut(&[f64]) -> (f64, Vec<f64>),
+    {
+        const C1: f64 = 1e-4;
+
+        let (loss, gradients) = match self.last.take() {
+            Some((last_parameters, loss, gradients)) if last_parameters == *parameters => {
+                (loss, gradients)
+            }
+            _ => closure(parameters),
+        };
+        if gradients.iter().all(|g| g.abs() <= self.tolerance_grad) {
+            self.last = Some((parameters.clone(), loss, gradients));
+            return loss;
+        }
+
+        let mut direction = self.direction(&gradients);
+        let mut slope = dot(&gradients, &direction);
+        if slope >= 0. {
+            // not a descent direction, start over from steepest descent
+            self.history.clear();
+            direction = gradients.iter().map(|g| -g).collect();
+            slope = dot(&gradients, &direction);
+        }
+        // without history the direction is not scaled, keep the first step small
+        let mut t = if self.history.is_empty() {
+            self.learning_rate * (1. / gradients.iter().map(|g| g.abs()).sum::<f64>()).min(1.)
+        } else {
+            self.learning_rate
+        };
+
+        for _ in 0..self.max_evals {
+            let next_parameters: Vec<f64> = parameters
+                .iter()
+                .zip(direction.iter())
+                .map(|(x, d)| x + t * d)
+                .collect();
+            let (next_loss, next_gradients) = closure(&next_parameters);
+            if next_loss <= loss + C1 * t * slope {
+                let s: Vec<f64> = direction.iter().map(|d| t * d).collect();
+                let y: Vec<f64> = next_gradients
+                    .iter()
+                    .zip(gradients.iter())
+                    .map(|(next, g)| next - g)
+                    .collect();
+                // keep only curvature pairs which keep H positive definite
+                let ys = dot(&y, &s);
+                if ys > 1e-10 {
+                    if self.history.len() == self.history_size {
+                        self.history.pop_front();
+                    }
+                    self.history.push_back((s, y, 1. / ys));
+                }
+                *parameters = next_parameters;
+                self.last = Some((parameters.clone(), next_loss, next_gradients));
+                return loss;
+            }
+            t *= 0.5;
+        }
+
+        // line search failed, stay and start over from steepest descent
+        self.history.clear();
+        self.last = Some((parameters.clone(), loss, gradients));
+        loss
+    }
+
+    fn learning_rate(&self) -> f64 {
+        self.learning_rate
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lbfgs() {
+        // rosenbrock, minimum 0 at (1, 1)
+        let rosenbrock = |x: &[f64]| {
+            let (a, b) = (x[0], x[1]);
+            (
+                (1. - a).powi(2) + 100. * (b - a * a).powi(2),
+                vec![-2. * (1. - a) - 400. * a * (b - a * a), 200. * (b - a * a)],
+            )
+        };
+        let mut lbfgs = LBFGS::new(1.);
+        let mut parameters = vec![-1.2, 1.];
+        for _ in 0..50 {
+            lbfgs.step(&mut parameters, rosenbrock);
+        }
+        assert!((parameters[0] - 1.).abs() < 1e-6);
+        assert!((parameters[1] - 1.).abs() < 1e-6);
+    }
+}
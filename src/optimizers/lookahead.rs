@@ -0,0 +1,107 @@
+use super::{layer_state, params, LayerState, Optimizer};
+
+pub struct Lookahead<O: Optimizer> {
+    optimizer: O,
+    k: usize,
+    alpha: f64,
+    counts: Vec<Option<usize>>,
+    slow_weights: Vec<Option<LayerState>>,
+}
+
+impl<O: Optimizer> Lookahead<O> {
+    pub fn new(optimizer: O) -> Lookahead<O> {
+        Lookahead {
+            optimizer,
+            k: 5,
+            alpha: 0.5,
+            counts: vec![],
+            slow_weights: vec![],
+        }
+    }
+
+    // fast steps of the wrapped optimizer between slow steps
+    pub fn with_k(mut self, k: usize) -> Lookahead<O> {
+        assert!(k > 0, "k should be positive");
+        self.k = k;
+        self
+    }
+
+    // slow step size
+    pub fn with_alpha(mut self, alpha: f64) -> Lookahead<O> {
+        self.alpha = alpha;
+        self
+    }
+
+    // the wrapped optimizer
+    pub fn inner(&self) -> &O {
+        &self.optimizer
+    }
+
+    pub fn inner_mut(&mut self) -> &mut O {
+        &mut self.optimizer
+    }
+}
+
+// https://arxiv.org/abs/1907.08610
+//
+// the wrapped optimizer updates the fast weights, and every k steps
+//     slow_weights = slow_weights + alpha * (fast_weights - slow_weights)
+//     fast_weights = slow_weights
+impl<O: Optimizer> Optimizer for Lookahead<O> {
+    fn learning_rate(&self) -> f64 {
+        self.optimizer.learning_rate()
+    }
+
+    fn set_learning_rate(&mut self, learning_rate: f64) {
+        self.optimizer.set_learning_rate(learning_rate);
+    }
+
+    fn optimize(
+        &mut self,
+        idx: usize,
+        weights: &mut [Vec<f64>],
+        bias: &mut [f64],
+        gradients: &mut [Vec<f64>],
+        bias_gradients: &mut [f64],
+    ) {
+        // slow weights start from the weights before the first step
+        let slow_weights = layer_state(&mut self.slow_weights, idx, || LayerState {
+            weights: weights.to_vec(),
+            bias: bias.to_vec(),
+        });
+
+        self.optimizer
+            .optimize(idx, weights, bias, gradients, bias_gradients);
+
+        let count = layer_state(&mut self.counts, idx, || 0);
+        *count += 1;
+        if *count % self.k == 0 {
+            let alpha = self.alpha;
+            params(weights, bias)
+                .zip(slow_weights.iter_mut())
+                .for_each(|(fast, slow)| {
+                    *slow += alpha * (*fast - *slow);
+                    *fast = *slow;
+                });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimizers::SGD;
+
+    #[test]
+    fn test_lookahead() {
+        let mut weights = vec![vec![1.]];
+        let mut bias = vec![0.];
+        let mut lookahead = Lookahead::new(SGD::new(0.1)).with_k(2);
+        for _ in 0..3 {
+            lookahead.optimize(0, &mut weights, &mut bias, &mut [vec![1.]], &mut [1.]);
+        }
+        // fast: 0.9, 0.8, slow: 1 + 0.5 * (0.8 - 1) = 0.9, fast: 0.8
+        assert!((weights[0][0] - 0.8).abs() < 1e-12);
+        assert!((bias[0] + 0.2).abs() < 1e-12);
+    }
+}
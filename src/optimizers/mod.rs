@@ -1,10 +1,36 @@
+mod adadelta;
+mod adagrad;
 mod adam;
+mod adamw;
+mod amsgrad;
+mod gradient_centralization;
+mod gradient_noise;
+mod lbfgs;
+mod lookahead;
+mod nadam;
+mod radam;
+mod rmsprop;
 mod sgd;
 
+pub use adadelta::Adadelta;
+pub use adagrad::Adagrad;
 pub use adam::Adam;
+pub use adamw::AdamW;
+pub use amsgrad::AMSGrad;
+pub use gradient_centralization::GradientCentralization;
+pub use gradient_noise::GradientNoise;
+pub use lbfgs::LBFGS;
+pub use lookahead::Lookahead;
+pub use nadam::Nadam;
+pub use radam::RAdam;
+pub use rmsprop::RMSProp;
 pub use sgd::SGD;
 
 pub trait Optimizer {
+    fn learning_rate(&self) -> f64;
+    // hook for learning rate schedulers, takes effect from the next optimize
+    fn set_learning_rate(&mut self, learning_rate: f64);
+
     // idx: layer index
     // weights: one layer's weights
     // bias: one layer's bias
@@ -18,4 +44,146 @@ pub trait Optimizer {
         gradients: &mut [Vec<f64>],
         bias_gradients: &mut [f64],
     );
+
+    // optimize one layer with the learning rate scaled by lr_multiplier,
+    // eg. a smaller one for pretrained layers while fine-tuning
+    fn optimize_with_lr_multiplier(
+        &mut self,
+        lr_multiplier: f64,
+        idx: usize,
+        weights: &mut [Vec<f64>],
+        bias: &mut [f64],
+        gradients: &mut [Vec<f64>],
+        bias_gradients: &mut [f64],
+    ) {
+        if lr_multiplier == 1. {
+            self.optimize(idx, weights, bias, gradients, bias_gradients);
+            return;
+        }
+        let learning_rate = self.learning_rate();
+        self.set_learning_rate(learning_rate * lr_multiplier);
+        self.optimize(idx, weights, bias, gradients, bias_gradients);
+        self.set_learning_rate(learning_rate);
+    }
+}
+
+// optimizers over all layers' parameters at once which re-evaluate the loss,
+// eg. quasi-Newton methods with line search, see Network::fit_with
+pub trait ClosureOptimizer {
+    // parameters: all layers' weights and bias flattened, updated in place
+    // closure: (loss, gradients) at the given parameters
+    // return: loss before this step
+    fn step<F>(&mut self, parameters: &mut Vec<f64>, closure: F) -> f64
+    where
+        F: FnMut(&[f64]) -> (f64, Vec<f64>);
+    // reported in the history of Network::fit_with
+    fn learning_rate(&self) -> f64;
+}
+
+// per-layer state of an optimizer, eg. running averages of gradients,
+// in the same shape as the layer's weights and bias
+pub(crate) struct LayerState {
+    weights: Vec<Vec<f64>>,
+    bias: Vec<f64>,
+}
+
+impl LayerState {
+    // init state default to value
+    pub(crate) fn new(gradients: &[Vec<f64>], bias_gradients: &[f64], value: f64) -> LayerState {
+        LayerState {
+            weights: gradients
+                .iter()
+                .map(|row| row.iter().map(|_| value).collect())
+                .collect(),
+            bias: bias_gradients.iter().map(|_| value).collect(),
+        }
+    }
+
+    // weights first then bias, same order as params and grads
+    pub(crate) fn iter_mut(&mut self) -> impl Iterator<Item = &mut f64> {
+        self.weights
+            .iter_mut()
+            .flatten()
+            .chain(self.bias.iter_mut())
+    }
+}
+
+// per-layer state is indexed by layer, but layers can be skipped, eg. frozen,
+// so a layer's state is created when the layer is first optimized, in any order
+pub(crate) fn layer_state<T, F>(states: &mut Vec<Option<T>>, idx: usize, init: F) -> &mut T
+where
+    F: FnOnce() -> T,
+{
+    if states.len() <= idx {
+        states.resize_with(idx + 1, || None);
+    }
+    states[idx].get_or_insert_with(init)
+}
+
+// one layer's weights and bias as a flat iterator
+pub(crate) fn params<'a>(
+    weights: &'a mut [Vec<f64>],
+    bias: &'a mut [f64],
+) -> impl Iterator<Item = &'a mut f64> {
+    weights.iter_mut().flatten().chain(bias.iter_mut())
+}
+
+// one layer's gradients and bias_gradients as a flat iterator
+pub(crate) fn grads<'a>(
+    gradients: &'a [Vec<f64>],
+    bias_gradients: &'a [f64],
+) -> impl Iterator<Item = f64> + 'a {
+    gradients
+        .iter()
+        .flatten()
+        .chain(bias_gradients.iter())
+        .cloned()
+}
+
+// decoupled weight decay, weights(t) = weights(t-1) * (1 - learning_rate * weight_decay)
+// applied to weights only, not bias
+pub(crate) fn decay_weights(weights: &mut [Vec<f64>], learning_rate: f64, weight_decay: f64) {
+    let decay = 1. - learning_rate * weight_decay;
+    weights
+        .iter_mut()
+        .for_each(|row| row.iter_mut().for_each(|weight| *weight *= decay));
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    // optimize weights [[0.5, -0.3]] and bias [0.1] with cycled gradients
+    // return: weights and bias flattened
+    pub(crate) fn optimize_steps<O: Optimizer>(optimizer: &mut O, steps: usize) -> Vec<f64> {
+        let gradients = [
+            (vec![vec![0.1, -0.2]], vec![0.3]),
+            (vec![vec![-0.05, 0.4]], vec![0.1]),
+            (vec![vec![0.2, 0.1]], vec![-0.2]),
+        ];
+        let mut weights = vec![vec![0.5, -0.3]];
+        let mut bias = vec![0.1];
+        for (mut gradient, mut bias_gradient) in gradients.iter().cloned().cycle().take(steps) {
+            optimizer.optimize(
+                0,
+                &mut weights,
+                &mut bias,
+                &mut gradient,
+                &mut bias_gradient,
+            );
+        }
+        params(&mut weights, &mut bias).map(|v| *v).collect()
+    }
+
+    pub(crate) fn assert_close(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len());
+        for (actual, expected) in actual.iter().zip(expected.iter()) {
+            assert!(
+                (actual - expected).abs() < 1e-12,
+                "{:?} != {:?}",
+                actual,
+                expected
+            );
+        }
+    }
 }
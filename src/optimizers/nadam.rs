@@ -0,0 +1,104 @@
+use super::{grads, layer_state, params, LayerState, Optimizer};
+
+pub struct Nadam {
+    pub learning_rate: f64,
+    beta1: f64,
+    beta2: f64,
+    eps: f64,
+    counts: Vec<Option<u64>>,
+    means: Vec<Option<LayerState>>,
+    virances: Vec<Option<LayerState>>,
+}
+
+impl Nadam {
+    pub fn new(learning_rate: f64) -> Nadam {
+        Nadam {
+            learning_rate,
+            beta1: 0.9,
+            beta2: 0.999,
+            eps: 1e-8,
+            counts: vec![],
+            means: vec![],
+            virances: vec![],
+        }
+    }
+
+    // decay rates of the running averages of gradients and squared gradients
+    pub fn with_betas(mut self, beta1: f64, beta2: f64) -> Nadam {
+        self.beta1 = beta1;
+        self.beta2 = beta2;
+        self
+    }
+
+    pub fn with_eps(mut self, eps: f64) -> Nadam {
+        self.eps = eps;
+        self
+    }
+}
+
+// https://openreview.net/forum?id=OM0jvwB8jIp57ZJjtNEZ
+//
+// Adam with nesterov momentum, without the momentum schedule of the paper
+impl Optimizer for Nadam {
+    fn learning_rate(&self) -> f64 {
+        self.learning_rate
+    }
+
+    fn set_learning_rate(&mut self, learning_rate: f64) {
+        self.learning_rate = learning_rate;
+    }
+
+    fn optimize(
+        &mut self,
+        idx: usize,
+        weights: &mut [Vec<f64>],
+        bias: &mut [f64],
+        gradients: &mut [Vec<f64>],
+        bias_gradients: &mut [f64],
+    ) {
+        // increased after every update of this layer
+        let count = layer_state(&mut self.counts, idx, || 0);
+        *count += 1;
+        let param = *count as f64;
+
+        let means = layer_state(&mut self.means, idx, || {
+            LayerState::new(gradients, bias_gradients, 0.)
+        });
+        let virances = layer_state(&mut self.virances, idx, || {
+            LayerState::new(gradients, bias_gradients, 0.)
+        });
+
+        let (learning_rate, beta1, beta2, eps) =
+            (self.learning_rate, self.beta1, self.beta2, self.eps);
+        params(weights, bias)
+            .zip(grads(gradients, bias_gradients))
+            .zip(means.iter_mut().zip(virances.iter_mut()))
+            .for_each(|((param_t, gradient), (mean, virance))| {
+                *mean = beta1 * *mean + (1. - beta1) * gradient;
+                *virance = beta2 * *virance + (1. - beta2) * gradient.powf(2.);
+                // look ahead: beta1 * mean_bias_corr(t) + (1 - beta1) * gradient_bias_corr(t)
+                let corr_mean =
+                    (beta1 * *mean + (1. - beta1) * gradient) / (1. - beta1.powf(param));
+                let corr_virance = *virance / (1. - beta2.powf(param));
+                *param_t -= learning_rate * corr_mean / (corr_virance.sqrt() + eps);
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimizers::tests::{assert_close, optimize_steps};
+
+    #[test]
+    fn test_nadam() {
+        assert_close(
+            &optimize_steps(&mut Nadam::new(0.1), 6),
+            &[
+                -0.009672301313599765,
+                -0.3597309030647576,
+                -0.3201671618967098,
+            ],
+        );
+    }
+}
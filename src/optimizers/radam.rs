@@ -0,0 +1,125 @@
+use super::{grads, layer_state, params, LayerState, Optimizer};
+
+pub struct RAdam {
+    pub learning_rate: f64,
+    beta1: f64,
+    beta2: f64,
+    eps: f64,
+    counts: Vec<Option<u64>>,
+    means: Vec<Option<LayerState>>,
+    virances: Vec<Option<LayerState>>,
+}
+
+impl RAdam {
+    pub fn new(learning_rate: f64) -> RAdam {
+        RAdam {
+            learning_rate,
+            beta1: 0.9,
+            beta2: 0.999,
+            eps: 1e-8,
+            counts: vec![],
+            means: vec![],
+            virances: vec![],
+        }
+    }
+
+    // decay rates of the running averages of gradients and squared gradients
+    pub fn with_betas(mut self, beta1: f64, beta2: f64) -> RAdam {
+        self.beta1 = beta1;
+        self.beta2 = beta2;
+        self
+    }
+
+    pub fn with_eps(mut self, eps: f64) -> RAdam {
+        self.eps = eps;
+        self
+    }
+}
+
+// https://arxiv.org/abs/1908.03265
+//
+// Adam with the virance rectified, falls back to SGD with momentum while the
+// virance is not tractable in the first steps
+impl Optimizer for RAdam {
+    fn learning_rate(&self) -> f64 {
+        self.learning_rate
+    }
+
+    fn set_learning_rate(&mut self, learning_rate: f64) {
+        self.learning_rate = learning_rate;
+    }
+
+    fn optimize(
+        &mut self,
+        idx: usize,
+        weights: &mut [Vec<f64>],
+        bias: &mut [f64],
+        gradients: &mut [Vec<f64>],
+        bias_gradients: &mut [f64],
+    ) {
+        // increased after every update of this layer
+        let count = layer_state(&mut self.counts, idx, || 0);
+        *count += 1;
+        let param = *count as f64;
+
+        let means = layer_state(&mut self.means, idx, || {
+            LayerState::new(gradients, bias_gradients, 0.)
+        });
+        let virances = layer_state(&mut self.virances, idx, || {
+            LayerState::new(gradients, bias_gradients, 0.)
+        });
+
+        let (learning_rate, beta1, beta2, eps) =
+            (self.learning_rate, self.beta1, self.beta2, self.eps);
+
+        // rho_inf = 2 / (1 - beta2) - 1
+        // rho(t) = rho_inf - 2 * t * beta2^t / (1 - beta2^t)
+        let rho_inf = 2. / (1. - beta2) - 1.;
+        let rho = rho_inf - 2. * param * beta2.powf(param) / (1. - beta2.powf(param));
+        // r(t) = sqrt((rho(t) - 4) * (rho(t) - 2) * rho_inf / ((rho_inf - 4) * (rho_inf - 2) * rho(t)))
+        let rectifier = if rho > 5. {
+            Some(
+                ((rho - 4.) * (rho - 2.) * rho_inf / ((rho_inf - 4.) * (rho_inf - 2.) * rho))
+                    .sqrt(),
+            )
+        } else {
+            None
+        };
+
+        params(weights, bias)
+            .zip(grads(gradients, bias_gradients))
+            .zip(means.iter_mut().zip(virances.iter_mut()))
+            .for_each(|((param_t, gradient), (mean, virance))| {
+                *mean = beta1 * *mean + (1. - beta1) * gradient;
+                *virance = beta2 * *virance + (1. - beta2) * gradient.powf(2.);
+                let corr_mean = *mean / (1. - beta1.powf(param));
+                match rectifier {
+                    Some(rectifier) => {
+                        // adaptive learning rate = sqrt(1 - beta2^t) / (virance(t).sqrt() + eps)
+                        let adaptive = (1. - beta2.powf(param)).sqrt() / (virance.sqrt() + eps);
+                        *param_t -= learning_rate * rectifier * corr_mean * adaptive;
+                    }
+                    None => *param_t -= learning_rate * corr_mean,
+                }
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimizers::tests::{assert_close, optimize_steps};
+
+    #[test]
+    fn test_radam() {
+        // beta2 = 0.9 reaches the rectified steps from step 6
+        assert_close(
+            &optimize_steps(&mut RAdam::new(0.1).with_betas(0.9, 0.9), 9),
+            &[
+                0.37569275414520414,
+                -0.3673428788086977,
+                -0.026196994179013992,
+            ],
+        );
+    }
+}
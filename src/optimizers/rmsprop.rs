@@ -0,0 +1,91 @@
+use super::{grads, layer_state, params, LayerState, Optimizer};
+
+pub struct RMSProp {
+    pub learning_rate: f64,
+    alpha: f64,
+    eps: f64,
+    squares: Vec<Option<LayerState>>,
+}
+
+impl RMSProp {
+    pub fn new(learning_rate: f64) -> RMSProp {
+        RMSProp {
+            learning_rate,
+            alpha: 0.99,
+            eps: 1e-8,
+            squares: vec![],
+        }
+    }
+
+    // decay rate of the running average of squared gradients
+    pub fn with_alpha(mut self, alpha: f64) -> RMSProp {
+        self.alpha = alpha;
+        self
+    }
+
+    pub fn with_eps(mut self, eps: f64) -> RMSProp {
+        self.eps = eps;
+        self
+    }
+}
+
+// http://www.cs.toronto.edu/~tijmen/csc321/slides/lecture_slides_lec6.pdf
+impl Optimizer for RMSProp {
+    fn learning_rate(&self) -> f64 {
+        self.learning_rate
+    }
+
+    fn set_learning_rate(&mut self, learning_rate: f64) {
+        self.learning_rate = learning_rate;
+    }
+
+    fn optimize(
+        &mut self,
+        idx: usize,
+        weights: &mut [Vec<f64>],
+        bias: &mut [f64],
+        gradients: &mut [Vec<f64>],
+        bias_gradients: &mut [f64],
+    ) {
+        let squares = layer_state(&mut self.squares, idx, || {
+            LayerState::new(gradients, bias_gradients, 0.)
+        });
+
+        let (learning_rate, alpha, eps) = (self.learning_rate, self.alpha, self.eps);
+        params(weights, bias)
+            .zip(grads(gradients, bias_gradients))
+            .zip(squares.iter_mut())
+            .for_each(|((param, gradient), square)| {
+                // square(t) = alpha * square(t-1) + (1 - alpha) * gradient(t)^2
+                *square = alpha * *square + (1. - alpha) * gradient.powf(2.);
+                // param(t) = param(t-1) - learning_rate * gradient(t) / (square(t).sqrt() + eps)
+                *param -= learning_rate * gradient / (square.sqrt() + eps);
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimizers::tests::{assert_close, optimize_steps};
+
+    #[test]
+    fn test_rmsprop() {
+        assert_close(
+            &optimize_steps(&mut RMSProp::new(0.1), 6),
+            &[
+                -1.7531080704314492,
+                -0.7975671595180862,
+                -1.1326522566424677,
+            ],
+        );
+        assert_close(
+            &optimize_steps(&mut RMSProp::new(0.1).with_alpha(0.9), 6),
+            &[
+                -0.23030439751544635,
+                -0.47441558283393065,
+                -0.28703158341967666,
+            ],
+        );
+    }
+}
@@ -1,31 +1,182 @@
-use super::Optimizer;
+use super::{decay_weights, layer_state, LayerState, Optimizer};
 use crate::functions::transform;
 
 pub struct SGD {
     pub learning_rate: f64,
+    momentum: f64,
+    dampening: f64,
+    nesterov: bool,
+    weight_decay: f64,
+    velocities: Vec<Option<LayerState>>,
 }
 
 impl SGD {
     pub fn new(learning_rate: f64) -> SGD {
-        SGD { learning_rate }
+        SGD {
+            learning_rate,
+            momentum: 0.,
+            dampening: 0.,
+            nesterov: false,
+            weight_decay: 0.,
+            velocities: vec![],
+        }
+    }
+
+    // velocity(t) = momentum * velocity(t-1) + (1 - dampening) * gradient(t)
+    // velocity(1) = gradient(1)
+    pub fn with_momentum(mut self, momentum: f64) -> SGD {
+        assert!(momentum >= 0., "momentum should not be negative");
+        self.momentum = momentum;
+        self
+    }
+
+    pub fn with_dampening(mut self, dampening: f64) -> SGD {
+        self.dampening = dampening;
+        self
+    }
+
+    // step with gradient(t) + momentum * velocity(t) instead of velocity(t)
+    pub fn with_nesterov(mut self) -> SGD {
+        self.nesterov = true;
+        self
+    }
+
+    // decoupled weight decay, weights(t) -= learning_rate * weight_decay * weights(t-1)
+    // applied to weights only, not bias
+    pub fn with_weight_decay(mut self, weight_decay: f64) -> SGD {
+        self.weight_decay = weight_decay;
+        self
     }
 }
 
+// https://pytorch.org/docs/stable/generated/torch.optim.SGD.html
+// https://arxiv.org/abs/1711.05101
 impl Optimizer for SGD {
+    fn learning_rate(&self) -> f64 {
+        self.learning_rate
+    }
+
+    fn set_learning_rate(&mut self, learning_rate: f64) {
+        self.learning_rate = learning_rate;
+    }
+
     fn optimize(
         &mut self,
-        _idx: usize,
+        idx: usize,
         weights: &mut [Vec<f64>],
         bias: &mut [f64],
         gradients: &mut [Vec<f64>],
         bias_gradients: &mut [f64],
     ) {
+        let learning_rate = self.learning_rate;
+
+        // step1. weights(t) = weights(t-1) * (1 - learning_rate * weight_decay)
+        if self.weight_decay != 0. {
+            decay_weights(weights, learning_rate, self.weight_decay);
+        }
+
+        if self.momentum == 0. {
+            transform(
+                weights,
+                gradients,
+                bias,
+                bias_gradients,
+                |weight_or_bias, gradient| *weight_or_bias -= learning_rate * gradient,
+            );
+            return;
+        }
+
+        // the first velocity is the gradient itself, without dampening
+        let first = self.velocities.get(idx).is_none_or(Option::is_none);
+        // init velocity default 0
+        let velocity = layer_state(&mut self.velocities, idx, || {
+            LayerState::new(gradients, bias_gradients, 0.)
+        });
+
+        let momentum = self.momentum;
+        let dampening = self.dampening;
+
+        // step2. velocity(t) = momentum * velocity(t-1) + (1 - dampening) * gradient(t)
+        transform(
+            &mut velocity.weights,
+            gradients,
+            &mut velocity.bias,
+            bias_gradients,
+            |velocity, gradient| {
+                *velocity = if first {
+                    gradient
+                } else {
+                    momentum * *velocity + (1. - dampening) * gradient
+                }
+            },
+        );
+
+        // step3. nesterov: gradient(t) = gradient(t) + momentum * velocity(t)
+        //        otherwise: gradient(t) = velocity(t)
+        let nesterov = self.nesterov;
+        transform(
+            gradients,
+            &velocity.weights,
+            bias_gradients,
+            &velocity.bias,
+            |gradient, velocity| {
+                *gradient = if nesterov {
+                    *gradient + momentum * velocity
+                } else {
+                    velocity
+                }
+            },
+        );
+
+        // step4. update weights and bias
         transform(
             weights,
             gradients,
             bias,
             bias_gradients,
-            |weight_or_bias, gradient| *weight_or_bias -= self.learning_rate * gradient,
+            |weight_or_bias, gradient| *weight_or_bias -= learning_rate * gradient,
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sgd_momentum() {
+        let mut weights = vec![vec![1.]];
+        let mut bias = vec![1.];
+        let mut sgd = SGD::new(0.1).with_momentum(0.9).with_weight_decay(0.5);
+        for _ in 0..2 {
+            sgd.optimize(0, &mut weights, &mut bias, &mut [vec![1.]], &mut [1.]);
+        }
+        // velocity: 1, 1.9
+        // weights: 1 * 0.95 - 0.1 = 0.85, 0.85 * 0.95 - 0.19 = 0.6175
+        assert!((weights[0][0] - 0.6175).abs() < 1e-12);
+        assert!((bias[0] - 0.71).abs() < 1e-12);
+
+        let mut weights = vec![vec![1.]];
+        let mut bias = vec![1.];
+        let mut sgd = SGD::new(0.1).with_momentum(0.9).with_nesterov();
+        for _ in 0..2 {
+            sgd.optimize(0, &mut weights, &mut bias, &mut [vec![1.]], &mut [1.]);
+        }
+        // gradient: 1 + 0.9 * 1 = 1.9, 1 + 0.9 * 1.9 = 2.71
+        assert!((weights[0][0] - 0.539).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_sgd_dampening() {
+        let mut weights = vec![vec![1.]];
+        let mut bias = vec![1.];
+        let mut sgd = SGD::new(0.1).with_momentum(0.9).with_dampening(0.5);
+        sgd.optimize(0, &mut weights, &mut bias, &mut [vec![1.]], &mut [1.]);
+        // velocity: 1, not dampened
+        assert!((weights[0][0] - 0.9).abs() < 1e-12);
+        sgd.optimize(0, &mut weights, &mut bias, &mut [vec![1.]], &mut [1.]);
+        // velocity: 0.9 * 1 + 0.5 * 1 = 1.4
+        assert!((weights[0][0] - 0.76).abs() < 1e-12);
+        assert!((bias[0] - 0.76).abs() < 1e-12);
+    }
+}
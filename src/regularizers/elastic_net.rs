@@ -0,0 +1,48 @@
+use super::{Regularizer, L1, L2};
+use crate::functions::transform_matrix;
+
+// https://web.stanford.edu/~hastie/Papers/B67.2%20(2005)%20301-320%20Zou%20&%20Hastie.pdf
+//
+// penalty = l1 * SUM(|w|) + l2 * SUM(w^2)
+#[derive(Debug)]
+pub struct ElasticNet {
+    l1: L1,
+    l2: L2,
+}
+
+impl ElasticNet {
+    pub fn new(l1: f64, l2: f64) -> ElasticNet {
+        ElasticNet {
+            l1: L1::new(l1),
+            l2: L2::new(l2),
+        }
+    }
+}
+
+impl Regularizer for ElasticNet {
+    fn penalty(&self, weights: &[Vec<f64>]) -> f64 {
+        self.l1.penalty(weights) + self.l2.penalty(weights)
+    }
+
+    fn gradient(&self, weights: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        let mut gradient = self.l1.gradient(weights);
+        transform_matrix(&mut gradient, &self.l2.gradient(weights), |g, l2| *g += l2);
+        gradient
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_elastic_net() {
+        let weights = vec![vec![0.5, -2.], vec![0., 1.5]];
+        let elastic_net = ElasticNet::new(0.1, 0.01);
+        assert!((elastic_net.penalty(&weights) - (0.1 * 4. + 0.01 * 6.5)).abs() < 1e-12);
+        assert_eq!(
+            elastic_net.gradient(&weights),
+            [vec![0.1 + 0.01, -0.1 - 0.04], vec![0., 0.1 + 0.03]]
+        );
+    }
+}
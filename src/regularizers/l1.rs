@@ -0,0 +1,39 @@
+use super::Regularizer;
+use crate::functions::for_each_matrix;
+
+// penalty = lambda * SUM(|w|), drives weights to exactly 0
+#[derive(Debug)]
+pub struct L1 {
+    lambda: f64,
+}
+
+impl L1 {
+    pub fn new(lambda: f64) -> L1 {
+        L1 { lambda }
+    }
+}
+
+impl Regularizer for L1 {
+    fn penalty(&self, weights: &[Vec<f64>]) -> f64 {
+        self.lambda * weights.iter().flatten().map(|w| w.abs()).sum::<f64>()
+    }
+
+    // subgradient, 0 at w = 0
+    fn gradient(&self, weights: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        let lambda = self.lambda;
+        for_each_matrix(weights, |w| if w == 0. { 0. } else { lambda * w.signum() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_l1() {
+        let weights = vec![vec![0.5, -2.], vec![0., 1.5]];
+        let l1 = L1::new(0.1);
+        assert!((l1.penalty(&weights) - 0.4).abs() < 1e-12);
+        assert_eq!(l1.gradient(&weights), [vec![0.1, -0.1], vec![0., 0.1]]);
+    }
+}
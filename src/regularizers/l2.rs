@@ -0,0 +1,43 @@
+use super::Regularizer;
+use crate::functions::for_each_matrix;
+
+// penalty = lambda * SUM(w^2), aka weight decay
+#[derive(Debug)]
+pub struct L2 {
+    lambda: f64,
+}
+
+impl L2 {
+    pub fn new(lambda: f64) -> L2 {
+        L2 { lambda }
+    }
+}
+
+impl Regularizer for L2 {
+    fn penalty(&self, weights: &[Vec<f64>]) -> f64 {
+        self.lambda * weights.iter().flatten().map(|w| w * w).sum::<f64>()
+    }
+
+    fn gradient(&self, weights: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        let lambda = self.lambda;
+        for_each_matrix(weights, |w| 2. * lambda * w)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_l2() {
+        let weights = vec![vec![0.5, -2.], vec![0., 1.5]];
+        let l2 = L2::new(0.1);
+        assert!((l2.penalty(&weights) - 0.65).abs() < 1e-12);
+        let expected = [[0.1, -0.4], [0., 0.3]];
+        for (row, expected) in l2.gradient(&weights).iter().zip(expected.iter()) {
+            for (g, expected) in row.iter().zip(expected.iter()) {
+                assert!((g - expected).abs() < 1e-12);
+            }
+        }
+    }
+}
@@ -0,0 +1,19 @@
+mod elastic_net;
+mod l1;
+mod l2;
+
+pub use elastic_net::ElasticNet;
+pub use l1::L1;
+pub use l2::L2;
+
+use std::fmt::Debug;
+
+// penalty on a layer's weights (not bias) added to the loss of each minibatch
+pub trait Regularizer: Debug {
+    // weights: one layer's weights
+    // return: penalty added to the mean loss
+    fn penalty(&self, weights: &[Vec<f64>]) -> f64;
+    // weights: one layer's weights
+    // return: d(penalty)/d(weights), added to the mean gradients
+    fn gradient(&self, weights: &[Vec<f64>]) -> Vec<Vec<f64>>;
+}
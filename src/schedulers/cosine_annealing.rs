@@ -0,0 +1,78 @@
+use super::LrScheduler;
+
+use std::f64::consts::PI;
+
+// https://arxiv.org/abs/1608.03983
+//
+// within a cycle of period epochs
+//     learning_rate = min + (base_learning_rate - min) * (1 + cos(PI * t / period)) / 2
+// then restarts from base_learning_rate with the period multiplied by period_mult
+pub struct CosineAnnealing {
+    period: usize,
+    period_mult: usize,
+    min_learning_rate: f64,
+}
+
+impl CosineAnnealing {
+    pub fn new(period: usize) -> CosineAnnealing {
+        assert!(period > 0, "period should be positive");
+        CosineAnnealing {
+            period,
+            period_mult: 1,
+            min_learning_rate: 0.,
+        }
+    }
+
+    pub fn with_period_mult(mut self, period_mult: usize) -> CosineAnnealing {
+        assert!(period_mult > 0, "period mult should be positive");
+        self.period_mult = period_mult;
+        self
+    }
+
+    pub fn with_min_learning_rate(mut self, min_learning_rate: f64) -> CosineAnnealing {
+        self.min_learning_rate = min_learning_rate;
+        self
+    }
+}
+
+impl LrScheduler for CosineAnnealing {
+    fn on_epoch(&mut self, epochs: usize, base_learning_rate: f64, _: f64, _: f64) -> Option<f64> {
+        // find the cycle of this epoch and the position within it
+        let mut t = epochs;
+        let mut period = self.period;
+        while t >= period {
+            t -= period;
+            period *= self.period_mult;
+        }
+        let min = self.min_learning_rate;
+        Some(min + (base_learning_rate - min) * (1. + (PI * t as f64 / period as f64).cos()) / 2.)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cosine_annealing() {
+        let mut cosine = CosineAnnealing::new(2)
+            .with_period_mult(2)
+            .with_min_learning_rate(0.1);
+        let learning_rates: Vec<f64> = (0..7)
+            .map(|epochs| cosine.on_epoch(epochs, 1., 1., 0.).unwrap())
+            .collect();
+        // cycles of 2 and 4 epochs
+        let expected = [
+            1.,
+            0.55,
+            1.,
+            0.55 + 0.45 * 0.5f64.sqrt(),
+            0.55,
+            0.55 - 0.45 * 0.5f64.sqrt(),
+            1.,
+        ];
+        for (lr, expected) in learning_rates.iter().zip(expected.iter()) {
+            assert!((lr - expected).abs() < 1e-12);
+        }
+    }
+}
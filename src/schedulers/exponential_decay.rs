@@ -0,0 +1,32 @@
+use super::LrScheduler;
+
+// learning_rate = base_learning_rate * gamma ^ epochs
+pub struct ExponentialDecay {
+    gamma: f64,
+}
+
+impl ExponentialDecay {
+    pub fn new(gamma: f64) -> ExponentialDecay {
+        ExponentialDecay { gamma }
+    }
+}
+
+impl LrScheduler for ExponentialDecay {
+    fn on_epoch(&mut self, epochs: usize, base_learning_rate: f64, _: f64, _: f64) -> Option<f64> {
+        Some(base_learning_rate * self.gamma.powi(epochs as i32))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exponential_decay() {
+        let mut exponential_decay = ExponentialDecay::new(0.5);
+        let learning_rates: Vec<f64> = (0..4)
+            .map(|epochs| exponential_decay.on_epoch(epochs, 2., 2., 0.).unwrap())
+            .collect();
+        assert_eq!(learning_rates, [2., 1., 0.5, 0.25]);
+    }
+}
@@ -0,0 +1,108 @@
+use super::LrScheduler;
+
+// https://arxiv.org/abs/1706.02677
+//
+// ramps the learning rate linearly from start_factor * base_learning_rate to
+// base_learning_rate over warmup_steps, then hands over to the next scheduler
+// which sees its steps and epochs counted from the end of warmup
+pub struct LinearWarmup {
+    warmup_steps: usize,
+    start_factor: f64,
+    next: Option<Box<dyn LrScheduler>>,
+    // steps taken so far
+    steps: usize,
+    // epochs done at the first epoch end after warmup
+    warmup_epochs: Option<usize>,
+}
+
+impl LinearWarmup {
+    pub fn new(warmup_steps: usize) -> LinearWarmup {
+        LinearWarmup {
+            warmup_steps,
+            start_factor: 0.,
+            next: None,
+            steps: 0,
+            warmup_epochs: None,
+        }
+    }
+
+    pub fn with_start_factor(mut self, start_factor: f64) -> LinearWarmup {
+        self.start_factor = start_factor;
+        self
+    }
+
+    pub fn then(mut self, next: Box<dyn LrScheduler>) -> LinearWarmup {
+        self.next = Some(next);
+        self
+    }
+}
+
+impl LrScheduler for LinearWarmup {
+    fn on_step(
+        &mut self,
+        steps: usize,
+        base_learning_rate: f64,
+        learning_rate: f64,
+    ) -> Option<f64> {
+        self.steps = steps + 1;
+        if steps < self.warmup_steps {
+            let progress = steps as f64 / self.warmup_steps as f64;
+            return Some(
+                base_learning_rate * (self.start_factor + (1. - self.start_factor) * progress),
+            );
+        }
+        let warmup_steps = self.warmup_steps;
+        let next = self
+            .next
+            .as_mut()
+            .and_then(|next| next.on_step(steps - warmup_steps, base_learning_rate, learning_rate));
+        if steps == self.warmup_steps {
+            // back to base_learning_rate unless the next scheduler says otherwise
+            next.or(Some(base_learning_rate))
+        } else {
+            next
+        }
+    }
+
+    fn on_epoch(
+        &mut self,
+        epochs: usize,
+        base_learning_rate: f64,
+        learning_rate: f64,
+        loss: f64,
+    ) -> Option<f64> {
+        if self.steps < self.warmup_steps {
+            return None;
+        }
+        // the first epoch to end after warmup is the next scheduler's epoch 0
+        let warmup_epochs = *self.warmup_epochs.get_or_insert(epochs);
+        self.next.as_mut().and_then(|next| {
+            next.on_epoch(
+                epochs - warmup_epochs,
+                base_learning_rate,
+                learning_rate,
+                loss,
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schedulers::StepDecay;
+
+    #[test]
+    fn test_linear_warmup() {
+        let mut warmup = LinearWarmup::new(4).then(Box::new(StepDecay::new(1, 0.5)));
+        let learning_rates: Vec<Option<f64>> =
+            (0..6).map(|steps| warmup.on_step(steps, 1., 1.)).collect();
+        assert_eq!(
+            learning_rates,
+            [Some(0.), Some(0.25), Some(0.5), Some(0.75), Some(1.), None]
+        );
+        // the epoch warmup finished in is epoch 0 of step decay
+        assert_eq!(warmup.on_epoch(2, 1., 1., 0.), Some(1.));
+        assert_eq!(warmup.on_epoch(3, 1., 1., 0.), Some(0.5));
+    }
+}
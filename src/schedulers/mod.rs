@@ -0,0 +1,45 @@
+mod cosine_annealing;
+mod exponential_decay;
+mod linear_warmup;
+mod one_cycle;
+mod reduce_on_plateau;
+mod step_decay;
+
+pub use cosine_annealing::CosineAnnealing;
+pub use exponential_decay::ExponentialDecay;
+pub use linear_warmup::LinearWarmup;
+pub use one_cycle::OneCycle;
+pub use reduce_on_plateau::ReduceOnPlateau;
+pub use step_decay::StepDecay;
+
+// drives the learning rate of any Optimizer through set_learning_rate
+// a scheduler implements the hook it is driven by, per step or per epoch
+pub trait LrScheduler {
+    // called before every optimizer step
+    // steps: optimizer steps taken so far
+    // base_learning_rate: optimizer's learning rate when the scheduler was set
+    // learning_rate: current learning rate
+    // return: learning rate for this step, None keeps the current one
+    fn on_step(
+        &mut self,
+        _steps: usize,
+        _base_learning_rate: f64,
+        _learning_rate: f64,
+    ) -> Option<f64> {
+        None
+    }
+
+    // called at the end of every epoch
+    // epochs: epochs done so far, including this one
+    // return: learning rate for the next epoch, None keeps the current one
+    // loss: validation loss of the epoch if there is one, otherwise training loss
+    fn on_epoch(
+        &mut self,
+        _epochs: usize,
+        _base_learning_rate: f64,
+        _learning_rate: f64,
+        _loss: f64,
+    ) -> Option<f64> {
+        None
+    }
+}
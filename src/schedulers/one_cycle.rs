@@ -0,0 +1,85 @@
+use super::LrScheduler;
+
+use std::f64::consts::PI;
+
+// https://arxiv.org/abs/1708.07120
+//
+// anneals the learning rate from max_learning_rate / div_factor up to
+// max_learning_rate over the first pct_start of total_steps, then down to
+// max_learning_rate / (div_factor * final_div_factor), both by cosine
+// the optimizer's own learning rate is ignored
+pub struct OneCycle {
+    max_learning_rate: f64,
+    total_steps: usize,
+    pct_start: f64,
+    div_factor: f64,
+    final_div_factor: f64,
+}
+
+impl OneCycle {
+    pub fn new(max_learning_rate: f64, total_steps: usize) -> OneCycle {
+        assert!(total_steps > 1, "one cycle needs at least 2 steps");
+        OneCycle {
+            max_learning_rate,
+            total_steps,
+            pct_start: 0.3,
+            div_factor: 25.,
+            final_div_factor: 1e4,
+        }
+    }
+
+    pub fn with_pct_start(mut self, pct_start: f64) -> OneCycle {
+        assert!(
+            pct_start > 0. && pct_start < 1.,
+            "pct start should be in (0, 1)"
+        );
+        self.pct_start = pct_start;
+        self
+    }
+
+    pub fn with_div_factors(mut self, div_factor: f64, final_div_factor: f64) -> OneCycle {
+        self.div_factor = div_factor;
+        self.final_div_factor = final_div_factor;
+        self
+    }
+
+    // learning rate of the step-th step, counted from 0
+    fn learning_rate(&self, step: usize) -> f64 {
+        let initial = self.max_learning_rate / self.div_factor;
+        let min = initial / self.final_div_factor;
+        let cos =
+            |start: f64, end: f64, pct: f64| end + (start - end) * (1. + (PI * pct).cos()) / 2.;
+
+        let last = (self.total_steps - 1) as f64;
+        let peak = (self.pct_start * last).max(1.);
+        let step = (step as f64).min(last);
+        if step <= peak {
+            cos(initial, self.max_learning_rate, step / peak)
+        } else {
+            cos(self.max_learning_rate, min, (step - peak) / (last - peak))
+        }
+    }
+}
+
+impl LrScheduler for OneCycle {
+    fn on_step(&mut self, steps: usize, _: f64, _: f64) -> Option<f64> {
+        Some(self.learning_rate(steps))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_one_cycle() {
+        let one_cycle = OneCycle::new(1., 11).with_pct_start(0.2);
+        assert!((one_cycle.learning_rate(0) - 0.04).abs() < 1e-12);
+        assert!((one_cycle.learning_rate(1) - 0.52).abs() < 1e-12);
+        assert!((one_cycle.learning_rate(2) - 1.).abs() < 1e-12);
+        assert!((one_cycle.learning_rate(6) - (0.04e-4 + 1.) / 2.).abs() < 1e-12);
+        assert!((one_cycle.learning_rate(10) - 0.04e-4).abs() < 1e-12);
+        // stays at the end after total steps
+        assert!((one_cycle.learning_rate(20) - 0.04e-4).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,93 @@
+use super::LrScheduler;
+
+// multiplies the learning rate by factor once the loss stopped improving by
+// more than threshold (relative) for patience epochs, then waits cooldown
+// epochs before counting again, never going below min_learning_rate
+pub struct ReduceOnPlateau {
+    factor: f64,
+    patience: usize,
+    threshold: f64,
+    cooldown: usize,
+    min_learning_rate: f64,
+    best: f64,
+    num_bad_epochs: usize,
+    cooldown_counter: usize,
+}
+
+impl ReduceOnPlateau {
+    pub fn new(factor: f64, patience: usize) -> ReduceOnPlateau {
+        assert!(factor > 0. && factor < 1., "factor should be in (0, 1)");
+        ReduceOnPlateau {
+            factor,
+            patience,
+            threshold: 1e-4,
+            cooldown: 0,
+            min_learning_rate: 0.,
+            best: f64::INFINITY,
+            num_bad_epochs: 0,
+            cooldown_counter: 0,
+        }
+    }
+
+    pub fn with_threshold(mut self, threshold: f64) -> ReduceOnPlateau {
+        self.threshold = threshold;
+        self
+    }
+
+    pub fn with_cooldown(mut self, cooldown: usize) -> ReduceOnPlateau {
+        self.cooldown = cooldown;
+        self
+    }
+
+    pub fn with_min_learning_rate(mut self, min_learning_rate: f64) -> ReduceOnPlateau {
+        self.min_learning_rate = min_learning_rate;
+        self
+    }
+}
+
+impl LrScheduler for ReduceOnPlateau {
+    fn on_epoch(&mut self, _: usize, _: f64, learning_rate: f64, loss: f64) -> Option<f64> {
+        if loss < self.best * (1. - self.threshold) {
+            self.best = loss;
+            self.num_bad_epochs = 0;
+        } else {
+            self.num_bad_epochs += 1;
+        }
+
+        if self.cooldown_counter > 0 {
+            self.cooldown_counter -= 1;
+            self.num_bad_epochs = 0;
+        }
+
+        if self.num_bad_epochs > self.patience {
+            self.cooldown_counter = self.cooldown;
+            self.num_bad_epochs = 0;
+            return Some((learning_rate * self.factor).max(self.min_learning_rate));
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reduce_on_plateau() {
+        let mut plateau = ReduceOnPlateau::new(0.5, 1).with_cooldown(1);
+        let losses = [1., 0.9, 0.9, 0.9, 0.9, 0.9, 0.8];
+        let mut learning_rate = 1.;
+        let learning_rates: Vec<f64> = losses
+            .iter()
+            .enumerate()
+            .map(|(epochs, &loss)| {
+                if let Some(lr) = plateau.on_epoch(epochs + 1, 1., learning_rate, loss) {
+                    learning_rate = lr;
+                }
+                learning_rate
+            })
+            .collect();
+        // reduce after 2 bad epochs, then 1 epoch of cooldown
+        assert_eq!(learning_rates, [1., 1., 1., 0.5, 0.5, 0.5, 0.5]);
+    }
+}
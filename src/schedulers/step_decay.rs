@@ -0,0 +1,34 @@
+use super::LrScheduler;
+
+// learning_rate = base_learning_rate * gamma ^ floor(epochs / step_size)
+pub struct StepDecay {
+    step_size: usize,
+    gamma: f64,
+}
+
+impl StepDecay {
+    pub fn new(step_size: usize, gamma: f64) -> StepDecay {
+        assert!(step_size > 0, "step size should be positive");
+        StepDecay { step_size, gamma }
+    }
+}
+
+impl LrScheduler for StepDecay {
+    fn on_epoch(&mut self, epochs: usize, base_learning_rate: f64, _: f64, _: f64) -> Option<f64> {
+        Some(base_learning_rate * self.gamma.powi((epochs / self.step_size) as i32))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_step_decay() {
+        let mut step_decay = StepDecay::new(2, 0.5);
+        let learning_rates: Vec<f64> = (0..5)
+            .map(|epochs| step_decay.on_epoch(epochs, 1., 1., 0.).unwrap())
+            .collect();
+        assert_eq!(learning_rates, [1., 1., 0.5, 0.5, 0.25]);
+    }
+}
@@ -0,0 +1,43 @@
+// data evaluated without training at the end of every epoch of fit
+pub enum Validation {
+    // held-out (inputs, expecteds)
+    Data(Vec<Vec<f64>>, Vec<Vec<f64>>),
+    // fraction of the samples passed to fit held out from training,
+    // taken from the end before shuffling
+    Split(f64),
+}
+
+impl Validation {
+    // number of samples held out from num_samples
+    pub(crate) fn num_held_out(&self, num_samples: usize) -> usize {
+        match *self {
+            Validation::Data(..) => 0,
+            Validation::Split(fraction) => {
+                assert!(
+                    fraction > 0. && fraction < 1.,
+                    "validation split should be in (0, 1)"
+                );
+                let num_held_out = (num_samples as f64 * fraction).round() as usize;
+                assert!(num_held_out > 0, "validation split holds out no samples");
+                num_held_out
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_num_held_out() {
+        assert_eq!(Validation::Split(0.25).num_held_out(8), 2);
+        assert_eq!(Validation::Split(0.1).num_held_out(5), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "validation split holds out no samples")]
+    fn test_empty_split() {
+        Validation::Split(0.05).num_held_out(8);
+    }
+}
@@ -0,0 +1,122 @@
+use crate::layers::Layer;
+
+// averages of all layers' weights and bias over training, which often
+// generalize better than the last weights
+// there is no batch normalization layer, so no statistics to recompute
+pub enum WeightAveraging {
+    // exponential moving average after every optimizer step
+    //     averaged(t) = decay * averaged(t-1) + (1 - decay) * weights(t)
+    Ema(f64),
+    // equal average after every epoch from start_epoch, counted over all fits
+    // https://arxiv.org/abs/1803.05407
+    Swa(usize),
+}
+
+// shadow copy of all layers' (weights, bias)
+pub(crate) struct AveragedWeights {
+    averaging: WeightAveraging,
+    pub(crate) weights: Vec<(Vec<Vec<f64>>, Vec<f64>)>,
+    // number of weights averaged so far
+    count: usize,
+}
+
+impl AveragedWeights {
+    pub(crate) fn new(averaging: WeightAveraging) -> AveragedWeights {
+        if let WeightAveraging::Ema(decay) = averaging {
+            assert!((0. ..1.).contains(&decay), "ema decay should be in [0, 1)");
+        }
+        AveragedWeights {
+            averaging,
+            weights: vec![],
+            count: 0,
+        }
+    }
+
+    // whether anything is averaged yet
+    pub(crate) fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    // start averaging again
+    pub(crate) fn reset(&mut self) {
+        self.weights.clear();
+        self.count = 0;
+    }
+
+    // called after every optimizer step
+    pub(crate) fn on_step(&mut self, layers: &[Box<Layer>]) {
+        if let WeightAveraging::Ema(decay) = self.averaging {
+            self.update(layers, decay);
+        }
+    }
+
+    // epochs: epochs done so far, including this one
+    pub(crate) fn on_epoch(&mut self, epochs: usize, layers: &[Box<Layer>]) {
+        if let WeightAveraging::Swa(start_epoch) = self.averaging {
+            if epochs > start_epoch {
+                // running mean, averaged(n) = averaged(n-1) + (weights - averaged(n-1)) / n
+                let decay = self.count as f64 / (self.count + 1) as f64;
+                self.update(layers, decay);
+            }
+        }
+    }
+
+    // averaged = decay * averaged + (1 - decay) * weights, starting from weights
+    fn update(&mut self, layers: &[Box<Layer>], decay: f64) {
+        if self.count == 0 {
+            self.weights = layers
+                .iter()
+                .map(|layer| (layer.weights.clone(), layer.bias.clone()))
+                .collect();
+        } else {
+            self.weights
+                .iter_mut()
+                .zip(layers.iter())
+                .for_each(|((weights, bias), layer)| {
+                    weights
+                        .iter_mut()
+                        .flatten()
+                        .chain(bias.iter_mut())
+                        .zip(layer.weights.iter().flatten().chain(layer.bias.iter()))
+                        .for_each(|(averaged, w)| *averaged = decay * *averaged + (1. - decay) * w);
+                });
+        }
+        self.count += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activators::Linear;
+
+    #[allow(clippy::vec_box)]
+    fn layers(weight: f64) -> Vec<Box<Layer>> {
+        vec![Box::new(Layer::new(
+            1,
+            1,
+            Box::new(Linear),
+            Some(vec![vec![weight]]),
+            Some(vec![-weight]),
+        ))]
+    }
+
+    #[test]
+    fn test_weight_averaging() {
+        let mut ema = AveragedWeights::new(WeightAveraging::Ema(0.5));
+        for weight in [1., 2., 4.] {
+            ema.on_step(&layers(weight));
+            ema.on_epoch(1, &layers(weight));
+        }
+        // 1, 0.5 * 1 + 0.5 * 2, 0.5 * 1.5 + 0.5 * 4
+        assert_eq!(ema.weights, [(vec![vec![2.75]], vec![-2.75])]);
+
+        let mut swa = AveragedWeights::new(WeightAveraging::Swa(1));
+        for (epochs, weight) in [1., 2., 4., 6.].iter().enumerate() {
+            swa.on_step(&layers(*weight));
+            swa.on_epoch(epochs + 1, &layers(*weight));
+        }
+        // epochs after the first
+        assert_eq!(swa.weights, [(vec![vec![4.]], vec![-4.])]);
+    }
+}